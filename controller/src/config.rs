@@ -1,5 +1,10 @@
+use std::time::Duration;
+
 #[derive(Debug)]
 pub struct WifiConfig {
     pub ssid: &'static str,
     pub password: &'static str,
 }
+
+/// How often the controller sends its sntp-synced time to the driver
+pub const TIME_SYNC_INTERVAL: Duration = Duration::from_secs(10 * 60);
@@ -1,7 +1,13 @@
-use std::{net::Ipv4Addr, string::FromUtf8Error};
+use std::{
+    net::Ipv4Addr,
+    string::FromUtf8Error,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use esp_idf_svc::{
-    hal::uart::UartDriver,
+    hal::{delay::NON_BLOCK, uart::UartDriver},
     http::{
         server::{Configuration, EspHttpConnection, EspHttpServer, Request},
         Method,
@@ -11,38 +17,50 @@ use esp_idf_svc::{
 };
 
 use log::info;
-use prolite::api::{Color, Command, Content, ContentDuration, ContentGroup, Repeat};
+use prolite::api::{
+    Color, Command, Content, ContentDuration, ContentGroup, DriverStatus, Repeat, Response,
+    Transition,
+};
 
 pub fn establish_control_server(
-    sender: UartDriver<'static>,
+    sender: Arc<Mutex<UartDriver<'static>>>,
     ip_address: Ipv4Addr,
 ) -> Result<EspHttpServer<'static>, EspError> {
     // this code modified from https://github.com/esp-rs/std-training/blob/main/intro/http-server/examples/http_server.rs
     let mut server = EspHttpServer::new(&Configuration::default()).map_err(|e| e.0)?;
 
-    // if this fails we will just restart
     let startup_command = Command::ShowNow {
         content: ContentGroup {
+            id: None,
             contents: vec![Content {
-                text: format!("IP: {}", ip_address),
+                text: format!("IP: {}", ip_address).into(),
                 color: Color::default(),
                 animation: prolite::api::Animation::None {
                     duration: ContentDuration::Forever,
                 },
-                align: prolite::api::Alignment::Center
+                align: prolite::api::Alignment::Center,
+                ..Default::default()
             }],
             repeat: Repeat::None,
+            transition: Transition::Cut,
         },
+        zone: None,
     };
 
-    send_command(&startup_command, &sender).unwrap();
+    // the driver may still be booting, in which case it just won't show the ip address
+    if let Err(e) = send_command(&startup_command, &sender) {
+        info!("[server] could not send startup command: {}", e);
+    }
 
+    let post_sender = sender.clone();
     server.fn_handler(
         "/api/",
         Method::Post,
         move |mut request| -> core::result::Result<(), EspIOError> {
-            let response_content = match process_request(&mut request, &sender) {
-                Ok(_) => "ok".to_owned(),
+            let response_content = match process_request(&mut request, &post_sender) {
+                Ok(Response::Accepted) => "ok".to_owned(),
+                Ok(Response::Rejected { error }) => format!("error: rejected by driver: {}", error),
+                Ok(Response::Status { status }) => format_status(&status),
                 Err(e) => format!("error: {}", e),
             };
 
@@ -54,13 +72,36 @@ pub fn establish_control_server(
         },
     )?;
 
+    server.fn_handler(
+        "/api/status",
+        Method::Get,
+        move |request| -> core::result::Result<(), EspIOError> {
+            let response_content = match send_command(&Command::GetStatus, &sender) {
+                Ok(Response::Status { status }) => format_status(&status),
+                Ok(response) => format!("error: unexpected response from driver: {:?}", response),
+                Err(e) => format!("error: {}", e),
+            };
+
+            let mut response = request.into_ok_response()?;
+            response.write_all(response_content.as_bytes())?;
+            Ok(())
+        },
+    )?;
+
     Ok(server)
 }
 
+fn format_status(status: &DriverStatus) -> String {
+    match serde_json::to_string(status) {
+        Ok(s) => s,
+        Err(e) => format!("error: could not serialize response: {}", e),
+    }
+}
+
 fn process_request(
     request: &mut Request<&mut EspHttpConnection>,
-    sender: &UartDriver<'static>,
-) -> Result<(), String> {
+    sender: &Mutex<UartDriver<'static>>,
+) -> Result<Response, String> {
     let request_content = match read_result(request) {
         Ok(Ok(s)) => s,
         Ok(Err(e)) => return Err(format!("could not decode request: {}", e)),
@@ -77,12 +118,63 @@ fn process_request(
     send_command(&command, sender)
 }
 
-fn send_command(command: &Command, sender: &UartDriver<'static>) -> Result<(), String> {
+/// Sends the system time, which must already have been set through sntp
+pub fn send_time(
+    sender: &Mutex<UartDriver<'static>>,
+    utc_offset_minutes: i32,
+) -> Result<(), String> {
+    let epoch = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(t) => t,
+        Err(e) => return Err(format!("system time is before the epoch: {}", e)),
+    };
+
+    let command = Command::SetTime {
+        epoch,
+        utc_offset_minutes,
+    };
+
+    match send_command(&command, sender)? {
+        Response::Accepted => Ok(()),
+        response => Err(format!("unexpected response from driver: {:?}", response)),
+    }
+}
+
+/// Whether the driver's status shows it doesn't know the time, e.g. because it was
+/// reset since the time was last sent
+pub fn driver_lost_time(sender: &Mutex<UartDriver<'static>>) -> bool {
+    match send_command(&Command::GetStatus, sender) {
+        Ok(Response::Status { status }) => status.time.is_none(),
+        Ok(response) => {
+            info!("[time] unexpected response from driver: {:?}", response);
+            false
+        }
+        Err(e) => {
+            info!("[time] could not get driver status: {}", e);
+            false
+        }
+    }
+}
+
+/// Sends a command to the driver, holding the lock until its response arrives
+pub fn send_command(
+    command: &Command,
+    sender: &Mutex<UartDriver<'static>>,
+) -> Result<Response, String> {
     let serialized_command = match serde_json::to_vec(command) {
         Ok(s) => s,
         Err(e) => return Err(format!("could not serialize request: {:?}", e)),
     };
 
+    let sender = match sender.lock() {
+        Ok(s) => s,
+        Err(e) => return Err(format!("could not lock uart: {}", e)),
+    };
+
+    // drop anything left over from a response we stopped waiting for
+    if let Err(e) = sender.clear_rx() {
+        return Err(format!("could not clear uart: {:?}", e));
+    }
+
     match sender.write(&serialized_command) {
         Ok(_) => { /* do nothing */ }
         Err(e) => return Err(format!("could not send request: {:?}", e)),
@@ -93,7 +185,42 @@ fn send_command(command: &Command, sender: &UartDriver<'static>) -> Result<(), S
         Err(e) => return Err(format!("could not send request: {:?}", e)),
     }
 
-    Ok(())
+    read_response(&sender)
+}
+
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn read_response(receiver: &UartDriver<'static>) -> Result<Response, String> {
+    let start = Instant::now();
+    let mut response = vec![];
+
+    while !response.ends_with(&prolite::uart::TERMINATION_SEQUENCE) {
+        if start.elapsed() > RESPONSE_TIMEOUT {
+            return Err("timed out waiting for a response from the driver".to_owned());
+        }
+
+        // wait a tiny while to see if more data is coming
+        thread::sleep(Duration::from_millis(10));
+
+        let bytes_to_read = match receiver.remaining_read() {
+            Ok(n) => n,
+            Err(e) => return Err(format!("could not receive response: {:?}", e)),
+        };
+
+        if bytes_to_read > 0 {
+            let mut buffer = vec![0; bytes_to_read];
+            match receiver.read(&mut buffer, NON_BLOCK) {
+                Ok(n) => response.extend_from_slice(&buffer[..n]),
+                Err(e) => return Err(format!("could not receive response: {:?}", e)),
+            }
+        }
+    }
+
+    let content_size = response.len() - prolite::uart::TERMINATION_SEQUENCE.len();
+    match serde_json::from_slice(&response[..content_size]) {
+        Ok(r) => Ok(r),
+        Err(e) => Err(format!("could not parse response: {}", e)),
+    }
 }
 
 const BUFFER_SIZE: usize = 512;
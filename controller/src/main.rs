@@ -1,12 +1,17 @@
-use std::{thread, time::Duration};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
 
-use config::WifiConfig;
+use config::{WifiConfig, TIME_SYNC_INTERVAL};
 use esp_idf_svc::{
     hal::{
         self,
         gpio::{AnyInputPin, AnyOutputPin},
         uart::{config::Config, UartDriver},
     },
+    sntp::SyncStatus,
     sys::EspError,
 };
 use log::info;
@@ -47,7 +52,18 @@ fn main() {
             .unwrap();
 
     let ip_address = connection.sta_netif().get_ip_info().unwrap().ip;
-    let mut _server = controller::establish_control_server(uart_tx, ip_address).unwrap();
+
+    // shared between the server's handlers and the time sync below
+    let uart_tx = Arc::new(Mutex::new(uart_tx));
+    let mut _server = controller::establish_control_server(uart_tx.clone(), ip_address).unwrap();
+
+    let utc_offset_minutes = env!("UTC_OFFSET_MINUTES")
+        .parse()
+        .expect("UTC_OFFSET_MINUTES should be a whole number of minutes");
+
+    let sntp = network::start_time_sync().unwrap();
+    let mut time_synced = false;
+    let mut last_time_sent: Option<Instant> = None;
 
     loop {
         retry(MAX_RETRY_ATTEMPTS, || {
@@ -59,6 +75,24 @@ fn main() {
         })
         .unwrap();
 
+        // the status only reads as completed once per sync
+        if sntp.get_sync_status() == SyncStatus::Completed {
+            time_synced = true;
+        }
+
+        // a driver that was reset doesn't know the time until it is sent again
+        let time_is_due = match last_time_sent {
+            Some(t) => t.elapsed() >= TIME_SYNC_INTERVAL || controller::driver_lost_time(&uart_tx),
+            None => true,
+        };
+
+        if time_synced && time_is_due {
+            match controller::send_time(&uart_tx, utc_offset_minutes) {
+                Ok(_) => last_time_sent = Some(Instant::now()),
+                Err(e) => info!("[time] could not send time to driver: {}", e),
+            }
+        }
+
         thread::sleep(Duration::from_secs(10));
     }
 }
@@ -1,6 +1,7 @@
 use esp_idf_svc::{
     eventloop::EspSystemEventLoop,
     hal::{modem::Modem, peripheral::Peripheral},
+    sntp::EspSntp,
     sys::{nvs_flash_init, EspError},
     wifi::{AuthMethod, BlockingWifi, ClientConfiguration, Configuration, EspWifi},
 };
@@ -86,3 +87,10 @@ pub fn establish_wifi_connection(
 
     Ok(esp_wifi)
 }
+
+/// Keeps the system time synced in the background; dropping the result stops syncing
+pub fn start_time_sync() -> Result<EspSntp<'static>, EspError> {
+    info!("[network] starting sntp...");
+
+    EspSntp::new_default()
+}
@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant};
+
+use log::info;
+
+// Syncs closer together than this are dominated by transmission delays, not drift
+const MIN_DRIFT_INTERVAL: Duration = Duration::from_secs(60);
+// Even a cheap crystal is off by less than this; anything more is the time being
+// corrected by hand, not the clock running off
+const MAX_DRIFT: f64 = 1000e-6;
+
+#[derive(Debug)]
+pub struct Clock {
+    boot_time: Instant,
+    last_sync: Option<TimeSync>,
+    utc_offset_minutes: i32,
+    /// (synced time elapsed - local time elapsed) / local time elapsed
+    drift: Option<f64>,
+}
+
+#[derive(Debug)]
+struct TimeSync {
+    at: Instant,
+    /// Time since the unix epoch at `at`
+    time: Duration,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self {
+            boot_time: Instant::now(),
+            last_sync: None,
+            utc_offset_minutes: 0,
+            drift: None,
+        }
+    }
+
+    pub fn uptime(&self, now: Instant) -> Duration {
+        now.checked_duration_since(self.boot_time)
+            .unwrap_or(Duration::ZERO)
+    }
+
+    pub fn set_time(&mut self, now: Instant, time: Duration, utc_offset_minutes: i32) {
+        if let Some(previous) = &self.last_sync {
+            let local_elapsed = now.saturating_duration_since(previous.at);
+
+            if local_elapsed >= MIN_DRIFT_INTERVAL {
+                let error = time.as_secs_f64() - self.time_since_epoch(now).unwrap().as_secs_f64();
+                info!(
+                    "[clock] off by {:.0}ms after {}s",
+                    error * 1000.0,
+                    local_elapsed.as_secs()
+                );
+
+                let synced_elapsed = time.as_secs_f64() - previous.time.as_secs_f64();
+                let drift =
+                    (synced_elapsed - local_elapsed.as_secs_f64()) / local_elapsed.as_secs_f64();
+
+                if drift.abs() < MAX_DRIFT {
+                    self.drift = Some(drift);
+                } else {
+                    info!("[clock] time was stepped, measuring drift again from here");
+                    self.drift = None;
+                }
+            }
+        }
+
+        self.last_sync = Some(TimeSync { at: now, time });
+        self.utc_offset_minutes = utc_offset_minutes;
+    }
+
+    /// Time since the unix epoch (UTC), or `None` if the time was never set.
+    /// Corrects for the drift measured between the last two syncs.
+    pub fn time_since_epoch(&self, now: Instant) -> Option<Duration> {
+        let sync = self.last_sync.as_ref()?;
+
+        let elapsed = now.saturating_duration_since(sync.at).as_secs_f64();
+        let corrected = elapsed * (1.0 + self.drift.unwrap_or(0.0));
+
+        Some(sync.time + Duration::from_secs_f64(corrected.max(0.0)))
+    }
+
+    /// Seconds since the unix epoch in local time, or `None` if the time was never set
+    pub fn local_time(&self, now: Instant) -> Option<i64> {
+        self.time_since_epoch(now)
+            .map(|t| t.as_secs() as i64 + self.utc_offset_minutes as i64 * 60)
+    }
+
+    pub fn utc_offset_minutes(&self) -> i32 {
+        self.utc_offset_minutes
+    }
+
+    pub fn drift_ppm(&self) -> Option<f64> {
+        self.drift.map(|d| d * 1_000_000.0)
+    }
+}
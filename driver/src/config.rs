@@ -4,6 +4,7 @@ use esp_idf_svc::hal::{
     self,
     gpio::{Level, PinDriver},
 };
+use prolite::ColorDepth;
 
 pub type RedPin = PinDriver<'static, hal::gpio::Gpio4, hal::gpio::Output>;
 pub type GreenPin = PinDriver<'static, hal::gpio::Gpio5, hal::gpio::Output>;
@@ -16,6 +17,8 @@ pub type ScreenPin = PinDriver<'static, hal::gpio::Gpio8, hal::gpio::Output>;
 pub const CLOCK_DELAY_US: u32 = 1;
 pub const ROW_DELAY_US: u32 = 200;
 pub const RENDER_FRAMERATE: Duration = Duration::from_micros(41667);
+// set to `ColorDepth::OnOff` if sub-frames make the display flicker or too slow
+pub const COLOR_DEPTH: ColorDepth = ColorDepth::Levels;
 
 pub const CONTROL_SIGNALS_BY_ROW: [(Level, Level, Level); 7] = [
     (Level::High, Level::High, Level::High),
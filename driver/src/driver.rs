@@ -1,18 +1,52 @@
 use esp_idf_svc::{hal::delay::Delay, sys::EspError};
-use prolite::ScreenBuffer;
+use prolite::{Level, ScreenBuffer};
 
 use crate::{
     config::*,
     gpio::{ControlPins, ToGpioLevel},
 };
 
+/// Draws one frame as sub-frames, one per bit of each pixel's levels (only the most
+/// significant one with `ColorDepth::OnOff`). Each sub-frame is lit twice as long as
+/// the one before it (binary-coded modulation), so a pixel is lit for a share of the
+/// frame proportional to its level.
 pub fn display_screen(
     buffer: &ScreenBuffer,
     delay_driver: &Delay,
     control_pins: &mut ControlPins,
+    brightness: u8,
+) -> Result<(), EspError> {
+    for plane in (Level::BITS - COLOR_DEPTH.bits())..Level::BITS {
+        // the most significant plane gets the full `ROW_DELAY_US`
+        let row_delay_us = (ROW_DELAY_US << plane) >> (Level::BITS - 1);
+        display_plane(
+            buffer,
+            delay_driver,
+            control_pins,
+            plane,
+            row_delay_us,
+            brightness,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Draws one bit plane. Each row is lit for a part of `row_delay_us` that depends on
+/// `brightness`, and blank for the rest, so every frame takes as long at any brightness.
+fn display_plane(
+    buffer: &ScreenBuffer,
+    delay_driver: &Delay,
+    control_pins: &mut ControlPins,
+    plane: u32,
+    row_delay_us: u32,
+    brightness: u8,
 ) -> Result<(), EspError> {
     let wait_clock_delay = || delay_driver.delay_us(CLOCK_DELAY_US);
 
+    let on_time_us = row_delay_us * brightness as u32 / u8::MAX as u32;
+    let off_time_us = row_delay_us - on_time_us;
+
     control_pins.clk.set_low()?;
     wait_clock_delay();
 
@@ -23,8 +57,8 @@ pub fn display_screen(
 
         for col in 0..ScreenBuffer::WIDTH {
             let pixel = buffer.0[row][80 - col - 1];
-            control_pins.r.set_level(pixel.red.to_gpio_level())?;
-            control_pins.g.set_level(pixel.green.to_gpio_level())?;
+            control_pins.r.set_level(pixel.red.to_gpio_level(plane))?;
+            control_pins.g.set_level(pixel.green.to_gpio_level(plane))?;
 
             control_pins.clk.set_high()?;
             wait_clock_delay();
@@ -38,9 +72,13 @@ pub fn display_screen(
 
         wait_clock_delay();
 
-        control_pins.screen.set_low()?;
-        delay_driver.delay_us(ROW_DELAY_US);
-        control_pins.screen.set_high()?;
+        if on_time_us > 0 {
+            control_pins.screen.set_low()?;
+            delay_driver.delay_us(on_time_us);
+            control_pins.screen.set_high()?;
+        }
+
+        delay_driver.delay_us(off_time_us);
 
         wait_clock_delay();
     }
@@ -15,16 +15,17 @@ pub struct ControlPins {
 }
 
 pub trait ToGpioLevel {
-    fn to_gpio_level(self) -> hal::gpio::Level;
+    fn to_gpio_level(self, plane: u32) -> hal::gpio::Level;
 }
 
 impl ToGpioLevel for Level {
-    // we wrote this so we can have multiple levels, not just low and high,
-    // but if we end up with just two levels, we can do away with this layer of abstraction
-    fn to_gpio_level(self) -> hal::gpio::Level {
-        match self {
-            Level::Off => hal::gpio::Level::Low,
-            Level::On => hal::gpio::Level::High,
+    // levels are drawn one bit plane at a time: the pin is high during the sub-frames
+    // whose bit is set in the level's intensity
+    fn to_gpio_level(self, plane: u32) -> hal::gpio::Level {
+        if self.intensity() & (1 << plane) != 0 {
+            hal::gpio::Level::High
+        } else {
+            hal::gpio::Level::Low
         }
     }
 }
@@ -1,6 +1,9 @@
 use std::{
-    collections::VecDeque,
-    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc,
+    },
     thread,
     time::{Duration, Instant},
 };
@@ -18,20 +21,21 @@ use esp_idf_svc::{
 };
 use gpio::ControlPins;
 use log::info;
+use player::Player;
 use prolite::{
-    api::{Animation, Color, Command, Content, ContentDuration},
+    api::{Animation, Color, Command, Content, ContentDuration, Response},
     ScreenBuffer,
 };
-use renderer::{
-    current_content::{ContentState, CurrentContent},
-    glyphs::get_glyph_placement,
-    UnknownGlyphBehavior,
-};
+use renderer::{glyphs::get_glyph_placement, UnknownGlyphBehavior};
 
+mod clock;
 mod config;
 mod driver;
 mod gpio;
+mod player;
+mod queue;
 mod renderer;
+mod zone;
 
 fn main() {
     // It is necessary to call this function once. Otherwise some patches to the runtime
@@ -62,7 +66,8 @@ fn main() {
         screen: PinDriver::output(peripherals.pins.gpio8).unwrap(),
     };
 
-    let uart_rx = UartDriver::new(
+    // tx goes back to the controller so it can report whether commands were accepted
+    let uart = UartDriver::new(
         peripherals.uart1,
         peripherals.pins.gpio14,
         peripherals.pins.gpio13,
@@ -73,18 +78,23 @@ fn main() {
     .unwrap();
 
     let (command_tx, command_rx) = mpsc::channel();
+    let (response_tx, response_rx) = mpsc::channel();
     let (buffer_tx, buffer_rx) = mpsc::channel();
 
     let delay_driver = Delay::new_default();
+    let brightness = Arc::new(AtomicU8::new(u8::MAX));
+    let renderer_brightness = brightness.clone();
 
     thread::Builder::new()
         .stack_size(8 * 1024)
-        .spawn(move || initialize_uart_thread(uart_rx, command_tx))
+        .spawn(move || initialize_uart_thread(uart, command_tx, response_rx))
         .unwrap();
 
     thread::Builder::new()
         .stack_size(8 * 1024)
-        .spawn(move || initialize_renderer_thread(command_rx, buffer_tx))
+        .spawn(move || {
+            initialize_renderer_thread(command_rx, response_tx, buffer_tx, renderer_brightness)
+        })
         .unwrap();
 
     let mut buffer = initial_buffer();
@@ -94,7 +104,9 @@ fn main() {
             Err(_) => { /* do nothing */ }
         }
 
-        match driver::display_screen(&buffer, &delay_driver, &mut control_pins) {
+        let level = brightness.load(Ordering::Relaxed);
+
+        match driver::display_screen(&buffer, &delay_driver, &mut control_pins, level) {
             Ok(_) => { /* do nothing */ }
             Err(e) => info!("[driver] error: {:?}", e),
         }
@@ -107,14 +119,16 @@ fn main() {
 
 fn initialize_renderer_thread(
     command_rx: Receiver<prolite::api::Command>,
+    response_tx: Sender<Response>,
     screen_buffer_tx: Sender<Box<ScreenBuffer>>,
+    brightness: Arc<AtomicU8>,
 ) {
-    let mut current_content = None;
-    let mut content_queue = Box::new(VecDeque::new());
+    let mut player = Box::new(Player::new(
+        UnknownGlyphBehavior::ReplaceWithPlaceholder,
+        brightness,
+    ));
     let mut now = Instant::now();
 
-    let behavior = UnknownGlyphBehavior::ReplaceWithPlaceholder;
-
     // Loop:
     // 1. Receive new commands
     // 2. Render command and update screen
@@ -122,55 +136,11 @@ fn initialize_renderer_thread(
     loop {
         if let Some(command) = try_recv(&command_rx) {
             info!("[render] received new command {:?}", &command);
-
-            match command {
-                prolite::api::Command::AddToQueue { content } => content_queue.push_back(content),
-                prolite::api::Command::ShowNow { content } => {
-                    content_queue.clear();
-                    current_content = Some(CurrentContent::new(content, behavior));
-                }
-                prolite::api::Command::Clear => {
-                    content_queue.clear();
-                    current_content = None;
-                }
-            }
+            send(&response_tx, player.handle_command(command, now));
         }
 
-        if current_content.is_none() {
-            if let Some(next_content) = content_queue.pop_front() {
-                current_content = Some(CurrentContent::new(next_content, behavior))
-            }
-        }
-
-        if let Some(cc) = current_content.as_mut() {
-            let should_render_current_frame;
-            let should_replace_current_content;
-
-            let u = cc.update(now);
-            match u {
-                ContentState::StepStarted => {
-                    should_render_current_frame = true;
-                    should_replace_current_content = false;
-                }
-                ContentState::StepIncomplete => {
-                    should_render_current_frame = cc.is_animated();
-                    should_replace_current_content = false;
-                }
-                ContentState::Finished => {
-                    should_render_current_frame = cc.is_animated();
-                    should_replace_current_content = true;
-                }
-            }
-
-            if should_render_current_frame {
-                let rendered = cc.render(now);
-                send(&screen_buffer_tx, rendered);
-            }
-
-            if should_replace_current_content {
-                info!("[render] finished rendering previous content");
-                current_content = None;
-            }
+        if let Some(rendered) = player.render_frame(now) {
+            send(&screen_buffer_tx, rendered);
         }
 
         let elapsed = now.elapsed();
@@ -185,21 +155,48 @@ fn initialize_renderer_thread(
 }
 
 fn initialize_uart_thread(
-    mut uart_receiver: UartDriver,
+    mut uart: UartDriver,
     buffer_sender: Sender<prolite::api::Command>,
+    response_receiver: Receiver<Response>,
 ) {
     info!("uart init");
 
     loop {
-        let read = read_next_command(&mut uart_receiver);
-        match read {
-            Ok(Ok(command)) => send(&buffer_sender, command),
-            Ok(Err(e)) => info!("[uart] failed to deserialize command: {}", e),
-            Err(e) => info!("[uart] failed to receive command: {}", e),
+        let read = read_next_command(&mut uart);
+        let response = match read {
+            Ok(Ok(command)) => {
+                send(&buffer_sender, command);
+                recv(&response_receiver)
+            }
+            Ok(Err(e)) => {
+                info!("[uart] failed to deserialize command: {}", e);
+                Response::Rejected {
+                    error: format!("could not parse command: {}", e),
+                }
+            }
+            Err(e) => {
+                info!("[uart] failed to receive command: {}", e);
+                continue;
+            }
+        };
+
+        if let Err(e) = send_response(&uart, &response) {
+            info!("[uart] failed to send response: {}", e);
         }
     }
 }
 
+fn send_response(uart: &UartDriver, response: &Response) -> Result<(), String> {
+    let serialized_response = serde_json::to_vec(response).map_err(|e| e.to_string())?;
+
+    uart.write(&serialized_response)
+        .map_err(|e| e.to_string())?;
+    uart.write(&prolite::uart::TERMINATION_SEQUENCE)
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
 fn read_next_command(
     uart_receiver: &mut UartDriver,
 ) -> Result<serde_json::Result<Command>, String> {
@@ -226,17 +223,28 @@ fn read_next_command(
 
 fn initial_buffer() -> Box<ScreenBuffer> {
     let content = Content {
-        text: "booting...".to_owned(),
+        text: "booting...".into(),
         color: Color::Orange,
         animation: Animation::None {
             duration: ContentDuration::Forever,
         },
         align: prolite::api::Alignment::Center,
+        ..Default::default()
     };
 
-    let rendered_glyphs = get_glyph_placement(&content.text, UnknownGlyphBehavior::Ignore);
-
-    renderer::render(&content, &rendered_glyphs, None, Duration::ZERO)
+    let rendered_glyphs = get_glyph_placement(
+        &content.text.to_spans(),
+        content.color,
+        UnknownGlyphBehavior::Ignore,
+    );
+
+    renderer::render(
+        &content,
+        &rendered_glyphs,
+        ScreenBuffer::WIDTH,
+        None,
+        Duration::ZERO,
+    )
 }
 
 fn send<T>(sender: &Sender<T>, value: T) {
@@ -245,6 +253,16 @@ fn send<T>(sender: &Sender<T>, value: T) {
     sender.send(value).unwrap()
 }
 
+fn recv<T>(receiver: &Receiver<T>) -> T {
+    match receiver.recv() {
+        Ok(x) => x,
+        Err(_) => {
+            // todo restart threads, but for now just panic
+            panic!("channel disconnected: {:?}", receiver)
+        }
+    }
+}
+
 fn try_recv<T>(receiver: &Receiver<T>) -> Option<T> {
     match receiver.try_recv() {
         Ok(x) => Some(x),
@@ -0,0 +1,256 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use log::info;
+use prolite::{
+    api::{Animation, Command, ContentGroup, DriverStatus, Repeat, Response, Span, ZoneLayout},
+    template::TemplateContext,
+    ScreenBuffer,
+};
+
+use crate::{clock::Clock, renderer::UnknownGlyphBehavior, zone::Zone};
+
+// Far more than anyone will watch, and small enough that the ticker's length can't
+// overflow
+const MAX_TICKER_REPEAT: usize = u16::MAX as usize;
+
+/// Everything that decides what is on screen: the zones it is split into, and what
+/// they all share.
+pub struct Player {
+    /// Never empty; commands without a zone go to the first one
+    zones: Vec<Zone>,
+    behavior: UnknownGlyphBehavior,
+    /// Set when the zones changed, so the whole screen is drawn again
+    force_render: bool,
+    clock: Clock,
+    /// Values for `{counter:name}` placeholders
+    counters: HashMap<String, i64>,
+    /// Shared with the display loop
+    brightness: Arc<AtomicU8>,
+}
+
+impl Player {
+    pub fn new(behavior: UnknownGlyphBehavior, brightness: Arc<AtomicU8>) -> Self {
+        Self {
+            zones: vec![Zone::new(ZoneLayout::default(), behavior)],
+            behavior,
+            force_render: false,
+            clock: Clock::new(),
+            counters: HashMap::new(),
+            brightness,
+        }
+    }
+
+    pub fn handle_command(&mut self, command: Command, now: Instant) -> Response {
+        let result = match command {
+            Command::AddToQueue { content, zone } => validate(&content)
+                .and_then(|_| self.zone(zone))
+                .map(|zone| zone.add_to_queue(content)),
+            Command::ShowNow { content, zone } => validate(&content)
+                .and_then(|_| self.zone(zone))
+                .map(|zone| zone.show_now(content)),
+            Command::Clear { zone } => self.zone(zone).map(Zone::clear),
+            Command::Remove { id, zone } => self.zone(zone).and_then(|zone| zone.remove(&id)),
+            Command::Replace { id, content, zone } => validate(&content)
+                .and_then(|_| self.zone(zone))
+                .and_then(|zone| zone.replace(&id, content)),
+            Command::Move { id, index, zone } => {
+                self.zone(zone).and_then(|zone| zone.move_to(&id, index))
+            }
+            Command::Insert {
+                index,
+                content,
+                zone,
+            } => validate(&content)
+                .and_then(|_| self.zone(zone))
+                .map(|zone| zone.insert(index, content)),
+            Command::Interrupt {
+                content,
+                priority,
+                resume,
+                zone,
+            } => validate(&content)
+                .and_then(|_| self.zone(zone))
+                .map(|zone| zone.interrupt(content, priority, resume, now)),
+            Command::SetCounter { name, value } => {
+                self.counters.insert(name, value);
+                Ok(())
+            }
+            Command::SetTime {
+                epoch,
+                utc_offset_minutes,
+            } => {
+                self.clock.set_time(now, epoch, utc_offset_minutes);
+                Ok(())
+            }
+            Command::SetBrightness { level } => {
+                self.brightness.store(level, Ordering::Relaxed);
+                Ok(())
+            }
+            Command::SetZones { zones } => validate_zones(&zones).map(|_| {
+                self.zones = zones
+                    .into_iter()
+                    .map(|layout| Zone::new(layout, self.behavior))
+                    .collect();
+                self.force_render = true;
+            }),
+            Command::GetStatus => {
+                return Response::Status {
+                    status: self.status(now),
+                }
+            }
+        };
+
+        match result {
+            Ok(()) => Response::Accepted,
+            Err(error) => {
+                info!("[render] rejected command: {}", error);
+                Response::Rejected { error }
+            }
+        }
+    }
+
+    /// The zone with this name, or the first one for `None`
+    fn zone(&mut self, name: Option<String>) -> Result<&mut Zone, String> {
+        match name {
+            Some(name) => self
+                .zones
+                .iter_mut()
+                .find(|zone| zone.name() == name)
+                .ok_or_else(|| format!("no zone named {}", name)),
+            None => Ok(&mut self.zones[0]),
+        }
+    }
+
+    pub fn status(&self, now: Instant) -> DriverStatus {
+        let first = self.zones[0].status();
+
+        DriverStatus {
+            queue_length: first.queue_length,
+            current: first.current,
+            interrupted: first.interrupted,
+            zones: self.zones.iter().map(Zone::status).collect(),
+            uptime: self.clock.uptime(now),
+            time: self.clock.time_since_epoch(now),
+            utc_offset_minutes: self.clock.utc_offset_minutes(),
+            clock_drift_ppm: self.clock.drift_ppm(),
+            brightness: self.brightness.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Advances the content in every zone, and returns a new frame if the screen
+    /// should change
+    pub fn render_frame(&mut self, now: Instant) -> Option<Box<ScreenBuffer>> {
+        let context = TemplateContext {
+            local_time: self.clock.local_time(now),
+            uptime: self.clock.uptime(now),
+            counters: &self.counters,
+        };
+
+        let mut changed = self.force_render;
+        for zone in &mut self.zones {
+            changed |= zone.render_frame(now, &context);
+        }
+
+        self.force_render = false;
+
+        if !changed {
+            return None;
+        }
+
+        let mut buffer = Box::new(ScreenBuffer::new());
+        for zone in &self.zones {
+            zone.copy_to_buffer(&mut buffer);
+        }
+
+        Some(buffer)
+    }
+}
+
+fn validate_zones(zones: &[ZoneLayout]) -> Result<(), String> {
+    if zones.is_empty() {
+        return Err("there has to be at least one zone".to_owned());
+    }
+
+    for (i, zone) in zones.iter().enumerate() {
+        if zone.width == 0 {
+            return Err(format!("zone {} has no width", zone.name));
+        }
+
+        if zone.width > ScreenBuffer::WIDTH || zone.x > ScreenBuffer::WIDTH - zone.width {
+            return Err(format!("zone {} does not fit on the screen", zone.name));
+        }
+
+        for other in &zones[..i] {
+            if other.name == zone.name {
+                return Err(format!("there is more than one zone named {}", zone.name));
+            }
+
+            if zone.x < other.x + other.width && other.x < zone.x + zone.width {
+                return Err(format!("zones {} and {} overlap", other.name, zone.name));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate(content: &ContentGroup) -> Result<(), String> {
+    if content.contents.is_empty() {
+        return Err("content group has no contents".to_owned());
+    }
+
+    for content in &content.contents {
+        let spans = content
+            .spans()
+            .map_err(|e| format!("invalid markup: {}", e))?;
+
+        for span in spans {
+            if let Span::Sprite { sprite } = span {
+                if sprite.frames.is_empty() {
+                    return Err("sprite has no frames".to_owned());
+                }
+            }
+        }
+
+        validate_animation(&content.animation)?;
+    }
+
+    Ok(())
+}
+
+fn validate_animation(animation: &Animation) -> Result<(), String> {
+    match animation {
+        Animation::Keyframes { keyframes }
+            if keyframes.windows(2).any(|pair| pair[0].time > pair[1].time) =>
+        {
+            return Err("keyframes are not in order of time".to_owned());
+        }
+        Animation::Typewriter { cps: 0, .. } => {
+            return Err("typewriter needs a cps of at least 1".to_owned());
+        }
+        Animation::Ticker {
+            repeat: Repeat::Times(n),
+            ..
+        } if *n > MAX_TICKER_REPEAT => {
+            return Err(format!(
+                "ticker can repeat at most {} times, use \"forever\" to loop without end",
+                MAX_TICKER_REPEAT
+            ));
+        }
+        Animation::Sequence { phases } => {
+            for phase in phases {
+                validate_animation(phase)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+
+use prolite::api::ContentGroup;
+
+#[derive(Debug, Default)]
+pub struct ContentQueue(VecDeque<ContentGroup>);
+
+impl ContentQueue {
+    pub fn new() -> Self {
+        Self(VecDeque::new())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn push_back(&mut self, content: ContentGroup) {
+        self.0.push_back(content)
+    }
+
+    pub fn pop_front(&mut self) -> Option<ContentGroup> {
+        self.0.pop_front()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Returns the number of groups removed
+    pub fn remove(&mut self, id: &str) -> usize {
+        let len = self.0.len();
+        self.0.retain(|c| !has_id(c, id));
+        len - self.0.len()
+    }
+
+    /// Returns the content back if there is no group with this id
+    pub fn replace(&mut self, id: &str, content: ContentGroup) -> Result<(), ContentGroup> {
+        match self.0.iter_mut().find(|c| has_id(c, id)) {
+            Some(existing) => {
+                *existing = content;
+                Ok(())
+            }
+            None => Err(content),
+        }
+    }
+
+    /// Returns false if there is no group with this id
+    pub fn move_to(&mut self, id: &str, index: usize) -> bool {
+        match self.0.iter().position(|c| has_id(c, id)) {
+            Some(position) => {
+                let content = self.0.remove(position).unwrap();
+                self.insert(index, content);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, content: ContentGroup) {
+        let index = index.min(self.0.len());
+        self.0.insert(index, content)
+    }
+}
+
+fn has_id(content: &ContentGroup, id: &str) -> bool {
+    content.id.as_deref() == Some(id)
+}
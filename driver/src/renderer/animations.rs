@@ -2,32 +2,42 @@ use std::time::Duration;
 
 use prolite::ScreenBuffer;
 
+use super::glyphs::{get_glyph, Glyph, RenderedGlyphs};
+
 use prolite::api::{
-    Alignment, Animation, ScrollPosition, SlideDirection, SlideInBoundsDirection, SlideType,
+    Alignment, Animation, ContentDuration, Easing, Hold, Keyframe, Overflow, Repeat,
+    ScrollPosition, SlideDirection, SlideInBoundsDirection, SlideSpeed, SlideType,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Offset {
     pub x: i32,
     pub y: i32,
 }
 
+/// Where to draw the text in an area `width` columns wide, such as a zone
 pub fn get_global_offset(
     animation: &Animation,
     default_alignment: Alignment,
-    rendered_width: usize,
+    rendered_glyphs: &RenderedGlyphs,
+    width: usize,
     duration: Option<Duration>,
     time_elapsed: Duration,
 ) -> Offset {
-    let default_offset = get_default_offset(default_alignment, rendered_width);
+    let rendered_width = rendered_glyphs.width;
+    let default_offset = get_default_offset(default_alignment, rendered_width, width);
 
     match animation {
         Animation::None { .. } => default_offset,
         Animation::Slide {
             slide_type,
             direction,
+            easing,
+            hold,
             ..
         } => {
+            let hold = get_hold(hold, rendered_glyphs);
+
             // not sure if making these into functions is even worth it,
             // it may be too complicated to optimize away
             let top_position = || Offset {
@@ -46,7 +56,7 @@ pub fn get_global_offset(
             };
 
             let right_position = || Offset {
-                x: ScreenBuffer::WIDTH as i32,
+                x: width as i32,
                 y: default_offset.y,
             };
 
@@ -57,45 +67,390 @@ pub fn get_global_offset(
                 SlideDirection::RightToLeft => (right_position(), left_position()),
             };
 
-            let (start_offset, end_offset) = match slide_type {
-                SlideType::In => (altered_start_offset, default_offset),
-                SlideType::Out => (default_offset, altered_end_offset),
-                SlideType::InOut => (altered_start_offset, altered_end_offset),
-            };
+            // the part of the duration spent moving
+            let duration = duration.unwrap_or_default().saturating_sub(hold);
 
-            let duration = duration.unwrap_or_default();
+            match slide_type {
+                SlideType::In => get_offset_for_movement(
+                    altered_start_offset,
+                    default_offset,
+                    duration,
+                    time_elapsed,
+                    *easing,
+                ),
+                SlideType::Out => match time_elapsed.checked_sub(hold) {
+                    Some(time_elapsed) => get_offset_for_movement(
+                        default_offset,
+                        altered_end_offset,
+                        duration,
+                        time_elapsed,
+                        *easing,
+                    ),
+                    None => default_offset,
+                },
+                SlideType::InOut if hold.is_zero() => get_offset_for_movement(
+                    altered_start_offset,
+                    altered_end_offset,
+                    duration,
+                    time_elapsed,
+                    *easing,
+                ),
+                SlideType::InOut => {
+                    // split the movement so both halves have the same speed
+                    let in_length = get_distance(altered_start_offset, default_offset);
+                    let out_length = get_distance(default_offset, altered_end_offset);
+                    let in_duration = duration.mul_f64(in_length / (in_length + out_length));
 
-            get_offset_for_linear_movement(start_offset, end_offset, duration, time_elapsed)
+                    if time_elapsed < in_duration {
+                        get_offset_for_movement(
+                            altered_start_offset,
+                            default_offset,
+                            in_duration,
+                            time_elapsed,
+                            *easing,
+                        )
+                    } else if time_elapsed < in_duration + hold {
+                        default_offset
+                    } else {
+                        get_offset_for_movement(
+                            default_offset,
+                            altered_end_offset,
+                            duration - in_duration,
+                            time_elapsed - in_duration - hold,
+                            *easing,
+                        )
+                    }
+                }
+            }
         }
-        Animation::SlideInBounds { direction, .. } => {
+        Animation::SlideInBounds {
+            direction,
+            speed,
+            easing,
+            pause_at_start,
+            pause_at_end,
+            ping_pong,
+            ..
+        } => {
             let get_alignment = match direction {
                 SlideInBoundsDirection::Forward => |position| Alignment::Left { position },
                 SlideInBoundsDirection::Reverse => |position| Alignment::Right { position },
             };
 
-            let start_offset =
-                get_default_offset(get_alignment(ScrollPosition::Beginning), rendered_width);
-            let end_offset = get_default_offset(get_alignment(ScrollPosition::End), rendered_width);
+            let start_offset = get_default_offset(
+                get_alignment(ScrollPosition::Beginning),
+                rendered_width,
+                width,
+            );
+            let end_offset =
+                get_default_offset(get_alignment(ScrollPosition::End), rendered_width, width);
+
+            let scroll_duration = get_scroll_duration(speed, rendered_width, width);
 
-            let duration = duration.unwrap_or_default();
+            let Some(time_elapsed) = time_elapsed.checked_sub(*pause_at_start) else {
+                return start_offset;
+            };
+
+            if time_elapsed < scroll_duration || !ping_pong {
+                return get_offset_for_movement(
+                    start_offset,
+                    end_offset,
+                    scroll_duration,
+                    time_elapsed,
+                    *easing,
+                );
+            }
 
-            get_offset_for_linear_movement(start_offset, end_offset, duration, time_elapsed)
+            match time_elapsed.checked_sub(scroll_duration + *pause_at_end) {
+                Some(time_elapsed) => get_offset_for_movement(
+                    end_offset,
+                    start_offset,
+                    scroll_duration,
+                    time_elapsed,
+                    *easing,
+                ),
+                None => end_offset,
+            }
         }
+        Animation::Ticker { gap, speed, .. } => {
+            let speed = get_ticker_speed(speed, *gap, rendered_width);
+            let moved = (time_elapsed.as_secs_f64() * speed).round() as i32;
+
+            Offset {
+                x: width as i32 - moved,
+                y: default_offset.y,
+            }
+        }
+        // text that doesn't fit starts at the left edge, and `get_typewriter_state`
+        // scrolls along from there
+        Animation::Typewriter { .. } if rendered_width > width => Offset {
+            x: 0,
+            y: default_offset.y,
+        },
+        Animation::Typewriter { .. } => default_offset,
+        Animation::Auto {
+            speed,
+            hold,
+            overflow,
+        } => get_global_offset(
+            &resolve_auto(
+                *speed,
+                get_hold(hold, rendered_glyphs),
+                *overflow,
+                rendered_width,
+                width,
+            ),
+            default_alignment,
+            rendered_glyphs,
+            width,
+            duration,
+            time_elapsed,
+        ),
+        Animation::Sequence { phases } => {
+            let shifts = get_phase_shifts(phases, default_alignment, rendered_glyphs, width);
+            let mut phase_start_time = Duration::ZERO;
+
+            for (i, (phase, shift)) in phases.iter().zip(shifts).enumerate() {
+                let phase_duration = get_animation_duration(phase, rendered_glyphs, width);
+                let time_elapsed = time_elapsed.saturating_sub(phase_start_time);
+
+                match phase_duration {
+                    Some(phase_duration)
+                        if time_elapsed >= phase_duration && i + 1 < phases.len() =>
+                    {
+                        phase_start_time = phase_start_time.saturating_add(phase_duration);
+                    }
+                    _ => {
+                        let offset = get_global_offset(
+                            phase,
+                            default_alignment,
+                            rendered_glyphs,
+                            width,
+                            phase_duration,
+                            time_elapsed,
+                        );
+
+                        return Offset {
+                            x: offset.x + shift.x,
+                            y: offset.y + shift.y,
+                        };
+                    }
+                }
+            }
+
+            default_offset
+        }
+        Animation::Keyframes { keyframes } => {
+            let get_position = |keyframe: &Keyframe| Offset {
+                x: default_offset.x + keyframe.x,
+                y: default_offset.y + keyframe.y,
+            };
+
+            match keyframes.iter().position(|k| k.time > time_elapsed) {
+                None => keyframes.last().map_or(default_offset, get_position),
+                Some(0) => get_position(&keyframes[0]),
+                Some(i) => {
+                    let (from, to) = (&keyframes[i - 1], &keyframes[i]);
+
+                    get_offset_for_movement(
+                        get_position(from),
+                        get_position(to),
+                        to.time - from.time,
+                        time_elapsed - from.time,
+                        to.easing,
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// How far to move each phase of a sequence so it joins up with its neighbours.
+/// Phases that set their own position stay where they are; the others follow the
+/// phase before them, or lead into the first phase that sets its own position.
+fn get_phase_shifts(
+    phases: &[Animation],
+    default_alignment: Alignment,
+    rendered_glyphs: &RenderedGlyphs,
+    width: usize,
+) -> Vec<Offset> {
+    let get_offset = |phase: &Animation, at_end: bool| {
+        let duration = get_animation_duration(phase, rendered_glyphs, width);
+        let time_elapsed = if at_end {
+            duration.unwrap_or_default()
+        } else {
+            Duration::ZERO
+        };
+
+        get_global_offset(
+            phase,
+            default_alignment,
+            rendered_glyphs,
+            width,
+            duration,
+            time_elapsed,
+        )
+    };
+
+    // the shift that moves `from` onto `to`
+    let join = |to: Offset, to_shift: Offset, from: Offset| Offset {
+        x: to.x + to_shift.x - from.x,
+        y: to.y + to_shift.y - from.y,
+    };
+
+    let mut shifts = vec![Offset { x: 0, y: 0 }; phases.len()];
+    let first_positioned = phases
+        .iter()
+        .position(|phase| sets_own_position(phase, rendered_glyphs, width));
+
+    let follow_from = match first_positioned {
+        Some(first_positioned) => {
+            for i in (0..first_positioned).rev() {
+                shifts[i] = join(
+                    get_offset(&phases[i + 1], false),
+                    shifts[i + 1],
+                    get_offset(&phases[i], true),
+                );
+            }
+
+            first_positioned + 1
+        }
+        None => 1,
+    };
+
+    for i in follow_from..phases.len() {
+        if !sets_own_position(&phases[i], rendered_glyphs, width) {
+            shifts[i] = join(
+                get_offset(&phases[i - 1], true),
+                shifts[i - 1],
+                get_offset(&phases[i], false),
+            );
+        }
+    }
+
+    shifts
+}
+
+/// Whether a phase of a sequence decides for itself where the text is, rather than
+/// moving it relative to where it's aligned
+fn sets_own_position(
+    animation: &Animation,
+    rendered_glyphs: &RenderedGlyphs,
+    width: usize,
+) -> bool {
+    match animation {
+        Animation::SlideInBounds { .. }
+        | Animation::Ticker { .. }
+        | Animation::Typewriter { .. } => true,
+        // see `resolve_auto`
+        Animation::Auto { .. } => rendered_glyphs.width > width,
+        _ => false,
+    }
+}
+
+/// Where to draw each copy of the text, from left to right. Only tickers have more
+/// than one copy; copies that are entirely outside of the `width` columns that are
+/// shown are left out.
+pub fn get_copy_offsets(
+    animation: &Animation,
+    rendered_width: usize,
+    width: usize,
+    offset: &Offset,
+) -> Vec<i32> {
+    let Animation::Ticker { gap, repeat, .. } = animation else {
+        return vec![offset.x];
+    };
+
+    let text_width = rendered_width as i32;
+    let period = (text_width + *gap as i32).max(1);
+    let copies = match repeat {
+        Repeat::None => 1,
+        Repeat::Times(n) => n.saturating_add(1),
+        Repeat::Forever => usize::MAX,
+    };
+
+    let first_visible = if offset.x + text_width > 0 {
+        0
+    } else {
+        (-(offset.x + text_width) / period + 1) as usize
+    };
+
+    (first_visible..copies)
+        .map(|i| offset.x + i as i32 * period)
+        .take_while(|x| *x < width as i32)
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct TypewriterState {
+    /// Glyphs from this column on haven't been typed yet
+    pub typed_width: usize,
+    /// The cursor, if it should be drawn at `typed_width` in this frame
+    pub cursor: Option<Glyph>,
+    /// How far to scroll left to keep the newest character on screen
+    pub scroll: i32,
+}
+
+pub fn get_typewriter_state(
+    animation: &Animation,
+    rendered_glyphs: &RenderedGlyphs,
+    width: usize,
+    offset: &Offset,
+    time_elapsed: Duration,
+) -> Option<TypewriterState> {
+    let Animation::Typewriter { cps, cursor, .. } = animation else {
+        return None;
+    };
+
+    let characters = rendered_glyphs.character_offsets();
+    let typed = (time_elapsed.as_secs_f64() * *cps as f64) as usize;
+    let finished = typed >= characters.len();
+
+    let typed_width = match characters.get(typed) {
+        Some(x_offset) => *x_offset,
+        // past the space after the last character
+        None => rendered_glyphs.width + 1,
+    };
+
+    // solid while typing, blinking twice a second afterwards
+    let cursor = cursor
+        .and_then(get_glyph)
+        .filter(|_| !finished || time_elapsed.as_millis() % 1000 < 500);
+
+    let cursor_width = cursor.map_or(0, |glyph| glyph.width());
+    let typed_end = offset.x + (typed_width + cursor_width) as i32;
+
+    Some(TypewriterState {
+        typed_width,
+        cursor,
+        scroll: (typed_end - width as i32).max(0),
+    })
+}
+
+fn get_typing_duration(cps: usize, rendered_glyphs: &RenderedGlyphs) -> Duration {
+    let characters = rendered_glyphs.character_offsets().len();
+    Duration::try_from_secs_f64(characters as f64 / cps as f64).unwrap_or(Duration::MAX)
+}
+
+/// How fast a ticker moves, in columns per second
+fn get_ticker_speed(speed: &SlideSpeed, gap: usize, rendered_width: usize) -> f64 {
+    match speed {
+        SlideSpeed::Duration(duration) => (rendered_width + gap) as f64 / duration.as_secs_f64(),
+        SlideSpeed::Dps(dps) => *dps as f64,
     }
 }
 
-pub fn get_default_offset(alignment: Alignment, rendered_width: usize) -> Offset {
+/// Where aligned text starts in an area `width` columns wide
+pub fn get_default_offset(alignment: Alignment, rendered_width: usize, width: usize) -> Offset {
     let x = match alignment {
         Alignment::Left { position } => {
-            if rendered_width <= ScreenBuffer::WIDTH {
+            if rendered_width <= width {
                 0
             } else {
                 match position {
                     ScrollPosition::Beginning => 0,
-                    ScrollPosition::Center => {
-                        (ScreenBuffer::WIDTH as i32 - rendered_width as i32) / 2
-                    }
-                    ScrollPosition::End => ScreenBuffer::WIDTH as i32 - rendered_width as i32,
+                    ScrollPosition::Center => (width as i32 - rendered_width as i32) / 2,
+                    ScrollPosition::End => width as i32 - rendered_width as i32,
                 }
             }
         }
@@ -106,7 +461,7 @@ pub fn get_default_offset(alignment: Alignment, rendered_width: usize) -> Offset
             // screen width = 8, width = 5 -> x = 1  W/2-(w+1)/2
             // screen width = 7, width = 4 -> x = 1  W/2-w/2
             // screen width = 7, width = 5 -> x = 1  W/2-w/2
-            let midpoint = (ScreenBuffer::WIDTH / 2) as i32;
+            let midpoint = (width / 2) as i32;
             if (midpoint & 1) ^ (w & 1) == 1 {
                 w += 1;
             }
@@ -114,14 +469,12 @@ pub fn get_default_offset(alignment: Alignment, rendered_width: usize) -> Offset
             midpoint - w / 2
         }
         Alignment::Right { position } => {
-            if rendered_width <= ScreenBuffer::WIDTH {
-                ScreenBuffer::WIDTH as i32 - rendered_width as i32
+            if rendered_width <= width {
+                width as i32 - rendered_width as i32
             } else {
                 match position {
-                    ScrollPosition::Beginning => ScreenBuffer::WIDTH as i32 - rendered_width as i32,
-                    ScrollPosition::Center => {
-                        (ScreenBuffer::WIDTH as i32 - rendered_width as i32) / 2
-                    }
+                    ScrollPosition::Beginning => width as i32 - rendered_width as i32,
+                    ScrollPosition::Center => (width as i32 - rendered_width as i32) / 2,
                     ScrollPosition::End => 0,
                 }
             }
@@ -131,19 +484,230 @@ pub fn get_default_offset(alignment: Alignment, rendered_width: usize) -> Offset
     Offset { x, y: 0 }
 }
 
-fn get_offset_for_linear_movement(
+fn get_offset_for_movement(
     start: Offset,
     end: Offset,
     duration: Duration,
     time_elapsed: Duration,
+    easing: Easing,
 ) -> Offset {
     if duration.is_zero() {
         return start;
     }
 
-    let progress = time_elapsed.div_duration_f32(duration);
+    let progress = ease(easing, time_elapsed.div_duration_f32(duration).min(1.0));
     let x = start.x + ((end.x - start.x) as f32 * progress).round() as i32;
     let y = start.y + ((end.y - start.y) as f32 * progress).round() as i32;
 
     Offset { x, y }
 }
+
+/// How long an animation takes in an area `width` columns wide, or `None` if it goes
+/// on forever
+pub fn get_animation_duration(
+    animation: &Animation,
+    rendered_glyphs: &RenderedGlyphs,
+    width: usize,
+) -> Option<Duration> {
+    let rendered_width = rendered_glyphs.width;
+
+    match *animation {
+        Animation::None { duration } => get_content_duration(duration, rendered_glyphs),
+        Animation::Slide {
+            speed,
+            direction,
+            slide_type,
+            hold,
+            ..
+        } => {
+            let animated_length = match (slide_type, direction) {
+                (
+                    SlideType::In | SlideType::Out,
+                    SlideDirection::TopToBottom | SlideDirection::BottomToTop,
+                ) => ScreenBuffer::HEIGHT,
+                (
+                    SlideType::In | SlideType::Out,
+                    SlideDirection::LeftToRight | SlideDirection::RightToLeft,
+                ) => (width + rendered_width) / 2,
+                (SlideType::InOut, SlideDirection::TopToBottom | SlideDirection::BottomToTop) => {
+                    2 * ScreenBuffer::HEIGHT
+                }
+                (SlideType::InOut, SlideDirection::LeftToRight | SlideDirection::RightToLeft) => {
+                    width + rendered_width
+                }
+            };
+
+            Some(
+                get_finite_animation_duration(&speed, animated_length)
+                    + get_hold(&hold, rendered_glyphs),
+            )
+        }
+        Animation::SlideInBounds {
+            speed,
+            pause_at_start,
+            pause_at_end,
+            ping_pong,
+            min_duration,
+            ..
+        } => {
+            let scroll_duration = get_scroll_duration(&speed, rendered_width, width);
+            let passes = if ping_pong { 2 } else { 1 };
+            let duration = pause_at_start + scroll_duration * passes + pause_at_end;
+
+            Some(duration.max(min_duration))
+        }
+        Animation::Ticker { gap, speed, repeat } => {
+            let copies = match repeat {
+                Repeat::None => 1,
+                Repeat::Times(n) => n.saturating_add(1),
+                Repeat::Forever => return None,
+            };
+
+            // until the last copy has left the screen
+            let distance = width
+                .saturating_add(copies.saturating_mul(rendered_width))
+                .saturating_add((copies - 1).saturating_mul(gap));
+            let speed = get_ticker_speed(&speed, gap, rendered_width);
+
+            // a ticker that doesn't move never finishes
+            Duration::try_from_secs_f64(distance as f64 / speed).ok()
+        }
+        Animation::Typewriter { cps, hold, .. } => hold.map(|hold| {
+            get_typing_duration(cps, rendered_glyphs)
+                .saturating_add(get_hold(&hold, rendered_glyphs))
+        }),
+        Animation::Keyframes { ref keyframes } => keyframes.last().map(|k| k.time),
+        Animation::Auto {
+            speed,
+            hold,
+            overflow,
+        } => get_animation_duration(
+            &resolve_auto(
+                speed,
+                get_hold(&hold, rendered_glyphs),
+                overflow,
+                rendered_width,
+                width,
+            ),
+            rendered_glyphs,
+            width,
+        ),
+        // a phase that never ends makes the whole sequence endless
+        Animation::Sequence { ref phases } => {
+            phases.iter().try_fold(Duration::ZERO, |total, phase| {
+                Some(total.saturating_add(get_animation_duration(phase, rendered_glyphs, width)?))
+            })
+        }
+    }
+}
+
+fn get_content_duration(
+    duration: ContentDuration,
+    rendered_glyphs: &RenderedGlyphs,
+) -> Option<Duration> {
+    match duration {
+        ContentDuration::Duration(duration) => Some(duration),
+        ContentDuration::Reading(reading) => {
+            Some(reading.duration(rendered_glyphs.characters, rendered_glyphs.words))
+        }
+        ContentDuration::Forever => None,
+    }
+}
+
+fn get_hold(hold: &Hold, rendered_glyphs: &RenderedGlyphs) -> Duration {
+    match hold {
+        Hold::Duration(duration) => *duration,
+        Hold::Reading { reading } => {
+            reading.duration(rendered_glyphs.characters, rendered_glyphs.words)
+        }
+    }
+}
+
+/// What `Animation::Auto` does with text this wide in an area `width` columns wide
+fn resolve_auto(
+    speed: SlideSpeed,
+    hold: Duration,
+    overflow: Overflow,
+    rendered_width: usize,
+    width: usize,
+) -> Animation {
+    if rendered_width <= width {
+        return Animation::None {
+            duration: ContentDuration::Duration(hold),
+        };
+    }
+
+    match overflow {
+        Overflow::SlideInBounds => Animation::SlideInBounds {
+            direction: SlideInBoundsDirection::default(),
+            speed,
+            easing: Easing::default(),
+            pause_at_start: hold,
+            pause_at_end: hold,
+            ping_pong: false,
+            min_duration: Duration::ZERO,
+        },
+        Overflow::Ticker { gap } => Animation::Ticker {
+            gap,
+            speed,
+            repeat: Repeat::None,
+        },
+    }
+}
+
+/// How long one pass of `SlideInBounds` takes
+fn get_scroll_duration(speed: &SlideSpeed, rendered_width: usize, width: usize) -> Duration {
+    get_finite_animation_duration(speed, rendered_width.saturating_sub(width))
+}
+
+fn get_finite_animation_duration(speed: &SlideSpeed, animated_length: usize) -> Duration {
+    match speed {
+        SlideSpeed::Duration(duration) => *duration,
+        SlideSpeed::Dps(dps) => Duration::from_secs_f64((animated_length as f64) / (*dps as f64)),
+    }
+}
+
+fn get_distance(a: Offset, b: Offset) -> f64 {
+    ((b.x - a.x).abs() + (b.y - a.y).abs()) as f64
+}
+
+/// Maps linear progress from 0 to 1 onto the easing curve, see https://easings.net
+fn ease(easing: Easing, t: f32) -> f32 {
+    match easing {
+        Easing::Linear => t,
+        Easing::EaseIn => t * t * t,
+        Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+        Easing::EaseInOut => {
+            if t < 0.5 {
+                4.0 * t * t * t
+            } else {
+                1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+            }
+        }
+        Easing::Bounce => {
+            const N: f32 = 7.5625;
+            const D: f32 = 2.75;
+
+            if t < 1.0 / D {
+                N * t * t
+            } else if t < 2.0 / D {
+                let t = t - 1.5 / D;
+                N * t * t + 0.75
+            } else if t < 2.5 / D {
+                let t = t - 2.25 / D;
+                N * t * t + 0.9375
+            } else {
+                let t = t - 2.625 / D;
+                N * t * t + 0.984375
+            }
+        }
+        Easing::Elastic => {
+            if t <= 0.0 || t >= 1.0 {
+                t
+            } else {
+                let c = 2.0 * std::f32::consts::PI / 3.0;
+                2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * c).sin() + 1.0
+            }
+        }
+    }
+}
@@ -1,14 +1,14 @@
 use std::time::{Duration, Instant};
 
+use log::info;
 use prolite::{
-    api::{
-        Animation, Content, ContentDuration, ContentGroup, Repeat, SlideDirection, SlideSpeed,
-        SlideType,
-    },
+    api::{Animation, Content, ContentDuration, ContentGroup, Repeat, Span, Transition},
+    template::TemplateContext,
     ScreenBuffer,
 };
 
 use super::{
+    animations::get_animation_duration,
     glyphs::{get_glyph_placement, RenderedGlyphs},
     UnknownGlyphBehavior,
 };
@@ -21,12 +21,30 @@ pub struct CurrentContent {
     pub step_duration: Option<Duration>,
     pub rendered_glyphs: RenderedGlyphs,
     behavior: UnknownGlyphBehavior,
+    /// How many columns the content is shown in
+    width: usize,
+    /// The current step's spans, with markup already parsed but placeholders and
+    /// sprites intact
+    spans: Vec<Span>,
+    /// What the placeholders and sprites evaluated to when the glyphs were last placed
+    evaluated_spans: Vec<Span>,
+
+    /// Set from the end of one step until the next one actually starts
+    transition: Option<ActiveTransition>,
 
     initialized: bool,
+    paused_at: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct ActiveTransition {
+    transition: Transition,
+    outgoing: Box<ScreenBuffer>,
+    start_time: Instant,
 }
 
 impl CurrentContent {
-    pub fn new(content_group: ContentGroup, behavior: UnknownGlyphBehavior) -> Self {
+    pub fn new(content_group: ContentGroup, behavior: UnknownGlyphBehavior, width: usize) -> Self {
         Self {
             content_group,
             step: 0,
@@ -35,23 +53,77 @@ impl CurrentContent {
             rendered_glyphs: RenderedGlyphs {
                 glyphs: vec![],
                 width: 0,
+                characters: 0,
+                words: 0,
             },
             behavior,
+            width,
+            spans: vec![],
+            evaluated_spans: vec![],
+            transition: None,
             initialized: false,
+            paused_at: None,
+        }
+    }
+
+    pub fn pause(&mut self, current_time: Instant) {
+        self.paused_at = Some(current_time);
+    }
+
+    /// Continues from where `pause` left off, as if no time had passed in between
+    pub fn resume(&mut self, current_time: Instant) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.step_start_time += current_time - paused_at;
+
+            if let Some(transition) = &mut self.transition {
+                transition.start_time += current_time - paused_at;
+            }
         }
     }
 
-    pub fn update(&mut self, current_time: Instant) -> ContentState {
+    pub fn restart(&mut self, current_time: Instant) {
+        self.step = 0;
+        self.step_start_time = current_time;
+        self.step_duration = None;
+        self.transition = None;
+        self.initialized = false;
+        self.paused_at = None;
+    }
+
+    pub fn update(&mut self, current_time: Instant, context: &TemplateContext) -> ContentState {
         if !self.initialized {
-            self.initialize_step();
+            self.initialize_step(current_time, context);
             self.initialized = true;
             return ContentState::StepStarted;
         }
 
+        if self.transition.is_some() {
+            if current_time < self.step_start_time {
+                return ContentState::StepIncomplete;
+            }
+
+            self.transition = None;
+            return ContentState::Changed;
+        }
+
         if self.step_duration.is_some()
-            && current_time - self.step_start_time > self.step_duration.unwrap()
+            && current_time.saturating_duration_since(self.step_start_time)
+                > self.step_duration.unwrap()
         {
-            return self.step();
+            return self.step(current_time, context);
+        }
+
+        if is_dynamic(&self.spans) {
+            let evaluated_spans =
+                evaluate_spans(&self.spans, current_time - self.step_start_time, context);
+
+            if evaluated_spans != self.evaluated_spans {
+                // keep the step start time, so with a speed in dps a slide carries on
+                // from the same position even though its total duration changes
+                self.evaluated_spans = evaluated_spans;
+                self.place_glyphs();
+                return ContentState::Changed;
+            }
         }
 
         return ContentState::StepIncomplete;
@@ -59,49 +131,110 @@ impl CurrentContent {
 
     pub fn is_animated(&self) -> bool {
         match self.content().animation {
-            Animation::None { .. } => false,
+            Animation::None { .. } => {
+                self.transition.is_some() || !self.content().effects.is_empty()
+            }
             _ => true,
         }
     }
 
     pub fn render(&self, current_time: Instant) -> Box<ScreenBuffer> {
-        super::render(
+        let frame = super::render(
             self.content(),
             &self.rendered_glyphs,
+            self.width,
             self.step_duration,
-            current_time - self.step_start_time,
-        )
-    }
+            current_time.saturating_duration_since(self.step_start_time),
+        );
 
-    fn step(&mut self) -> ContentState {
-        if self.step + 1 < self.content_group.contents.len() {
-            self.step += 1;
-            self.initialize_step();
-            return ContentState::StepStarted;
+        match &self.transition {
+            Some(transition) => super::render_transition(
+                &transition.outgoing,
+                &frame,
+                &transition.transition,
+                self.width,
+                current_time.saturating_duration_since(transition.start_time),
+            ),
+            None => frame,
         }
+    }
 
-        match self.content_group.repeat {
-            Repeat::None | Repeat::Times(0) => ContentState::Finished,
-            Repeat::Times(n) => {
-                self.content_group.repeat = Repeat::Times(n - 1);
-
-                self.step = 0;
-                self.initialize_step();
-                ContentState::StepStarted
-            }
-            Repeat::Forever => {
-                self.step = 0;
-                self.initialize_step();
-                ContentState::StepStarted
+    fn step(&mut self, current_time: Instant, context: &TemplateContext) -> ContentState {
+        let next_step = if self.step + 1 < self.content_group.contents.len() {
+            self.step + 1
+        } else {
+            match self.content_group.repeat {
+                Repeat::None | Repeat::Times(0) => return ContentState::Finished,
+                Repeat::Times(n) => {
+                    self.content_group.repeat = Repeat::Times(n - 1);
+                    0
+                }
+                Repeat::Forever => 0,
             }
+        };
+
+        let step_end_time = self.step_start_time + self.step_duration.unwrap_or_default();
+        let outgoing = self.render(step_end_time);
+
+        self.step = next_step;
+        self.initialize_step(current_time, context);
+
+        let transition = self
+            .content()
+            .transition
+            .unwrap_or(self.content_group.transition);
+
+        if transition != Transition::Cut {
+            // the step itself only starts once the transition is over
+            self.step_start_time += transition.duration();
+            self.transition = Some(ActiveTransition {
+                transition,
+                outgoing,
+                start_time: step_end_time,
+            });
         }
+
+        ContentState::StepStarted
     }
 
-    fn initialize_step(&mut self) {
-        self.rendered_glyphs =
-            get_glyph_placement(&self.content_group.contents[self.step].text, self.behavior);
+    fn initialize_step(&mut self, current_time: Instant, context: &TemplateContext) {
+        let content = &self.content_group.contents[self.step];
+        self.spans = content.spans().unwrap_or_else(|e| {
+            info!("[render] invalid markup, showing it as plain text: {}", e);
+            content.text.to_spans()
+        });
+
         self.step_start_time = self.step_start_time + self.step_duration.unwrap_or(Duration::ZERO);
-        self.step_duration = get_duration(self.content(), self.rendered_glyphs.width);
+        self.evaluated_spans = evaluate_spans(
+            &self.spans,
+            current_time.saturating_duration_since(self.step_start_time),
+            context,
+        );
+        self.place_glyphs();
+    }
+
+    fn place_glyphs(&mut self) {
+        let content = &self.content_group.contents[self.step];
+        self.rendered_glyphs =
+            get_glyph_placement(&self.evaluated_spans, content.color, self.behavior);
+        self.step_duration = get_duration(
+            self.content(),
+            &self.spans,
+            &self.rendered_glyphs,
+            self.width,
+        );
+    }
+
+    pub fn step_index(&self) -> usize {
+        self.step
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.content_group.contents.len()
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.content_group.id.as_deref()
     }
 
     pub fn content(&self) -> &Content {
@@ -113,57 +246,61 @@ impl CurrentContent {
 pub enum ContentState {
     StepStarted,
     StepIncomplete,
+    /// Still in the same step, but a placeholder shows different text or a sprite
+    /// moved on to another frame
+    Changed,
     Finished,
 }
 
-fn get_duration(content: &Content, rendered_width: usize) -> Option<Duration> {
-    match content.animation {
-        Animation::None { duration } => match duration {
-            ContentDuration::Duration(duration) => Some(duration),
-            ContentDuration::Forever => None,
-        },
-        Animation::Slide {
-            speed,
-            direction,
-            slide_type,
-        } => {
-            let animated_length = match (slide_type, direction) {
-                (
-                    SlideType::In | SlideType::Out,
-                    SlideDirection::TopToBottom | SlideDirection::BottomToTop,
-                ) => ScreenBuffer::HEIGHT,
-                (
-                    SlideType::In | SlideType::Out,
-                    SlideDirection::LeftToRight | SlideDirection::RightToLeft,
-                ) => (ScreenBuffer::WIDTH + rendered_width) / 2,
-                (SlideType::InOut, SlideDirection::TopToBottom | SlideDirection::BottomToTop) => {
-                    2 * ScreenBuffer::HEIGHT
-                }
-                (SlideType::InOut, SlideDirection::LeftToRight | SlideDirection::RightToLeft) => {
-                    ScreenBuffer::WIDTH + rendered_width
-                }
-            };
+fn is_dynamic(spans: &[Span]) -> bool {
+    spans
+        .iter()
+        .any(|span| matches!(span, Span::Placeholder { .. } | Span::Sprite { .. }))
+}
 
-            Some(get_finite_animation_duration(&speed, animated_length))
-        }
-        Animation::SlideInBounds {
-            direction: _,
-            speed,
-        } => {
-            let animated_length = if rendered_width > ScreenBuffer::WIDTH {
-                rendered_width - ScreenBuffer::WIDTH
-            } else {
-                0
-            };
-
-            Some(get_finite_animation_duration(&speed, animated_length))
-        }
-    }
+/// Replaces every placeholder with a text span in the same color, and every sprite
+/// with its frame `elapsed` into the step
+fn evaluate_spans(spans: &[Span], elapsed: Duration, context: &TemplateContext) -> Vec<Span> {
+    spans
+        .iter()
+        .map(|span| match span {
+            Span::Placeholder { placeholder, color } => Span::Text {
+                text: placeholder.evaluate(context),
+                color: *color,
+            },
+            Span::Sprite { sprite } => match sprite.frame_at(elapsed) {
+                Some(frame) => Span::Bitmap {
+                    bitmap: frame.clone(),
+                },
+                None => Span::Text {
+                    text: String::new(),
+                    color: None,
+                },
+            },
+            span => span.clone(),
+        })
+        .collect()
 }
 
-fn get_finite_animation_duration(speed: &SlideSpeed, animated_length: usize) -> Duration {
-    match speed {
-        SlideSpeed::Duration(duration) => *duration,
-        SlideSpeed::Dps(dps) => Duration::from_secs_f64((animated_length as f64) / (*dps as f64)),
+fn get_duration(
+    content: &Content,
+    spans: &[Span],
+    rendered_glyphs: &RenderedGlyphs,
+    width: usize,
+) -> Option<Duration> {
+    match content.animation {
+        // a step that would show forever ends once its sprites have played,
+        // unless one of them loops forever
+        Animation::None {
+            duration: ContentDuration::Forever,
+        } => spans
+            .iter()
+            .filter_map(|span| match span {
+                Span::Sprite { sprite } => Some(sprite.duration()),
+                _ => None,
+            })
+            .reduce(|a, b| a.zip(b).map(|(a, b)| a.max(b)))
+            .flatten(),
+        _ => get_animation_duration(&content.animation, rendered_glyphs, width),
     }
 }
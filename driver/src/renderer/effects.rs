@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use prolite::api::{Color, Effect};
+
+const RAINBOW: [Color; 3] = [Color::Red, Color::Orange, Color::Green];
+
+/// Whether to draw anything at all, which only a blink turns off
+pub fn is_visible(effects: &[Effect], time_elapsed: Duration) -> bool {
+    effects.iter().all(|effect| match effect {
+        Effect::Blink { period, duty_cycle } => {
+            period.is_zero()
+                || (time_elapsed.as_secs_f64() / period.as_secs_f64()).fract() < *duty_cycle
+        }
+        _ => true,
+    })
+}
+
+/// The color to draw the glyph at `index` in, instead of its own `color`
+pub fn get_color(effects: &[Effect], color: Color, index: usize, time_elapsed: Duration) -> Color {
+    effects.iter().fold(color, |color, effect| match effect {
+        Effect::Blink { .. } => color,
+        Effect::Alternate { colors, interval } if !colors.is_empty() => {
+            colors[get_cycle(time_elapsed, *interval) % colors.len()]
+        }
+        Effect::Alternate { .. } => color,
+        Effect::Rainbow { interval } => {
+            RAINBOW[(index + get_cycle(time_elapsed, *interval)) % RAINBOW.len()]
+        }
+    })
+}
+
+/// How many intervals have passed
+fn get_cycle(time_elapsed: Duration, interval: Duration) -> usize {
+    if interval.is_zero() {
+        return 0;
+    }
+
+    (time_elapsed.as_nanos() / interval.as_nanos()) as usize
+}
@@ -16,6 +16,14 @@ pub const PLACEHOLDER_GLYPH: Glyph = Glyph {
 
 pub const EMPTY_GLYPH: Glyph = Glyph { data: 0 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphMode {
+    /// Writes the glyph's pixels and leaves the rest of the buffer alone
+    Normal,
+    /// Turns the glyph's pixels off, cutting it out of whatever was drawn before
+    Inverted,
+}
+
 impl Glyph {
     pub const fn width(&self) -> usize {
         if self.data >> 63 == 1 {
@@ -33,7 +41,35 @@ impl Glyph {
         Self { data }
     }
 
-    pub fn copy_to_buffer(&self, buffer: &mut ScreenBuffer, pixel: Pixel, x: i32, y: i32) {
+    /// Builds a glyph from up to 9 columns, with bit 0 of each column being the top row
+    pub fn from_columns(columns: &[u8]) -> Self {
+        let width = columns.len();
+        debug_assert!(width > 0 && width <= 9);
+
+        let mut data = 0;
+        for row in 0..7 {
+            for column in columns {
+                data = (data << 1) | ((*column as u64 >> row) & 1);
+            }
+        }
+
+        if width == 9 {
+            data |= 1 << 63;
+        } else {
+            data |= ((width - 1) as u64) << 60;
+        }
+
+        Self { data }
+    }
+
+    pub fn copy_to_buffer(
+        &self,
+        buffer: &mut ScreenBuffer,
+        pixel: Pixel,
+        mode: GlyphMode,
+        x: i32,
+        y: i32,
+    ) {
         let width = self.width();
         let mut data = self.data;
 
@@ -41,9 +77,13 @@ impl Glyph {
             for col in (0..(width as i32)).rev() {
                 let x = x + col;
                 let y = y + row;
+                let lit = data & 1 == 1;
 
-                if data & 1 == 1 {
-                    buffer.set_if_in_bounds(y, x, pixel);
+                if lit {
+                    match mode {
+                        GlyphMode::Normal => buffer.set_if_in_bounds(y, x, pixel),
+                        GlyphMode::Inverted => buffer.set_if_in_bounds(y, x, Pixel::OFF),
+                    }
                 }
 
                 data >>= 1;
@@ -0,0 +1,36 @@
+// Names usable as `{icon:name}` in markup or `{"icon": "name"}` in spans.
+// Each one maps to a character that already has a glyph in glyphs*.txt.
+const ICONS: [(&str, char); 22] = [
+    ("heart", '♥'),
+    ("heart_outline", '♡'),
+    ("star", '★'),
+    ("note", '♪'),
+    ("notes", '♫'),
+    ("sun", '☀'),
+    ("umbrella", '☂'),
+    ("smile", '☺'),
+    ("phone", '☎'),
+    ("spade", '♠'),
+    ("club", '♣'),
+    ("diamond", '♦'),
+    ("dot", '●'),
+    ("circle", '○'),
+    ("square", '■'),
+    ("square_outline", '□'),
+    ("triangle_up", '▲'),
+    ("triangle_down", '▼'),
+    ("arrow_up", '↑'),
+    ("arrow_down", '↓'),
+    ("arrow_left", '←'),
+    ("arrow_right", '→'),
+];
+
+const EXTRA_ICONS: [(&str, char); 1] = [("fox", '🦊')];
+
+pub fn get_icon_char(name: &str) -> Option<char> {
+    ICONS
+        .iter()
+        .chain(EXTRA_ICONS.iter())
+        .find(|(icon, _)| *icon == name)
+        .map(|(_, c)| *c)
+}
@@ -1,14 +1,20 @@
 mod generated;
 mod generated_extra;
 mod glyph;
+mod icons;
+
+use prolite::{
+    api::{Color, Span},
+    bitmap::Bitmap,
+};
 
 use generated::CHARS;
 use generated::CHARS_MAX;
 use generated_extra::CHARS_EXTRA;
 use glyph::EMPTY_GLYPH;
-pub use glyph::{Glyph, PLACEHOLDER_GLYPH};
+pub use glyph::{Glyph, GlyphMode, PLACEHOLDER_GLYPH};
 
-fn get_glyph(c: char) -> Option<Glyph> {
+pub fn get_glyph(c: char) -> Option<Glyph> {
     let codepoint = c as usize;
 
     if codepoint >= CHARS_MAX {
@@ -21,40 +27,80 @@ fn get_glyph(c: char) -> Option<Glyph> {
     }
 }
 
-pub fn get_glyph_placement(text: &str, behavior: UnknownGlyphBehavior) -> RenderedGlyphs {
-    let mut width = 0;
+pub fn get_glyph_placement(
+    spans: &[Span],
+    default_color: Color,
+    behavior: UnknownGlyphBehavior,
+) -> RenderedGlyphs {
+    let mut rendered = RenderedGlyphs {
+        glyphs: vec![],
+        width: 0,
+        characters: 0,
+        words: 0,
+    };
+
+    for span in spans {
+        let color = span.color().unwrap_or(default_color);
+
+        match span {
+            Span::Text { text, .. } => {
+                for c in text.chars() {
+                    rendered.place(get_glyph(c), color, behavior);
+                }
+
+                rendered.characters += text.chars().filter(|c| !c.is_whitespace()).count();
+                rendered.words += text.split_whitespace().count();
+            }
+            Span::Icon { icon, .. } => {
+                rendered.place(
+                    icons::get_icon_char(icon).and_then(get_glyph),
+                    color,
+                    behavior,
+                );
+
+                rendered.characters += 1;
+                rendered.words += 1;
+            }
+            // these have to be evaluated into text or bitmaps before placing glyphs
+            Span::Placeholder { .. } | Span::Sprite { .. } => { /* do nothing */ }
+            Span::Bitmap { bitmap } => rendered.place_bitmap(bitmap),
+        }
+    }
+
+    // remove the extra spaced added at the end
+    rendered.width = rendered.width.saturating_sub(1);
+    rendered
+}
+
+/// The rows that are lit in a color, given a column's red and green channels
+type ColorMask = fn(u8, u8) -> u8;
+
+const BITMAP_COLORS: [(Color, ColorMask); 3] = [
+    (Color::Red, |r, g| r & !g),
+    (Color::Green, |r, g| !r & g),
+    (Color::Orange, |r, g| r & g),
+];
+
+/// Splits a bitmap into glyphs up to 9 columns wide, with one glyph per color in each
+fn get_bitmap_glyphs(bitmap: &Bitmap) -> Vec<RenderedGlyph> {
     let mut glyphs = vec![];
 
-    for c in text.chars() {
-        match get_glyph(c) {
-            Some(glyph) => {
+    for (chunk, (red, green)) in bitmap.red.chunks(9).zip(bitmap.green.chunks(9)).enumerate() {
+        for (color, mask) in BITMAP_COLORS {
+            let columns: Vec<u8> = red.iter().zip(green).map(|(r, g)| mask(*r, *g)).collect();
+
+            if columns.iter().any(|c| *c != 0) {
                 glyphs.push(RenderedGlyph {
-                    glyph,
-                    x_offset: width,
+                    glyph: Glyph::from_columns(&columns),
+                    x_offset: chunk * 9,
+                    color,
+                    from_bitmap: true,
                 });
-                width += glyph.width()
-            }
-            None => {
-                match behavior {
-                    UnknownGlyphBehavior::ReplaceWithPlaceholder => {
-                        glyphs.push(RenderedGlyph {
-                            glyph: PLACEHOLDER_GLYPH,
-                            x_offset: width,
-                        });
-                        width += PLACEHOLDER_GLYPH.width()
-                    }
-                    UnknownGlyphBehavior::Ignore => { /* do nothing */ }
-                }
             }
         }
-
-        // add spaces between characters
-        width += 1
     }
 
-    // remove the extra spaced added at the end
-    width -= 1;
-    RenderedGlyphs { glyphs, width }
+    glyphs
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -67,10 +113,66 @@ pub enum UnknownGlyphBehavior {
 pub struct RenderedGlyph {
     pub glyph: Glyph,
     pub x_offset: usize,
+    pub color: Color,
+    /// Part of a bitmap or sprite, which keeps its own colors whatever the effects
+    pub from_bitmap: bool,
 }
 
 #[derive(Debug)]
 pub struct RenderedGlyphs {
     pub glyphs: Vec<RenderedGlyph>,
     pub width: usize,
+    /// How much there is to read, for `ReadingTime`
+    pub characters: usize,
+    pub words: usize,
+}
+
+impl RenderedGlyphs {
+    /// Where each character starts, in order. Glyphs at the same offset, like the
+    /// colors of a bitmap, count as one character.
+    pub fn character_offsets(&self) -> Vec<usize> {
+        let mut offsets: Vec<usize> = self.glyphs.iter().map(|g| g.x_offset).collect();
+        offsets.dedup();
+        offsets
+    }
+
+    fn place(&mut self, glyph: Option<Glyph>, color: Color, behavior: UnknownGlyphBehavior) {
+        match glyph {
+            Some(glyph) => {
+                self.glyphs.push(RenderedGlyph {
+                    glyph,
+                    x_offset: self.width,
+                    color,
+                    from_bitmap: false,
+                });
+                self.width += glyph.width()
+            }
+            None => {
+                match behavior {
+                    UnknownGlyphBehavior::ReplaceWithPlaceholder => {
+                        self.glyphs.push(RenderedGlyph {
+                            glyph: PLACEHOLDER_GLYPH,
+                            x_offset: self.width,
+                            color,
+                            from_bitmap: false,
+                        });
+                        self.width += PLACEHOLDER_GLYPH.width()
+                    }
+                    UnknownGlyphBehavior::Ignore => { /* do nothing */ }
+                }
+            }
+        }
+
+        // add spaces between characters
+        self.width += 1
+    }
+
+    fn place_bitmap(&mut self, bitmap: &Bitmap) {
+        for mut glyph in get_bitmap_glyphs(bitmap) {
+            glyph.x_offset += self.width;
+            self.glyphs.push(glyph);
+        }
+
+        self.width += bitmap.width() + 1
+    }
 }
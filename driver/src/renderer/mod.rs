@@ -1,40 +1,162 @@
 use std::time::Duration;
 
-use glyphs::RenderedGlyphs;
+use animations::TypewriterState;
 pub use glyphs::UnknownGlyphBehavior;
+use glyphs::{GlyphMode, RenderedGlyphs};
 
-use prolite::{api::Content, Pixel, ScreenBuffer};
+use prolite::{
+    api::{Content, Transition},
+    Pixel, ScreenBuffer,
+};
+
+use crate::config::COLOR_DEPTH;
 
 mod animations;
 pub mod current_content;
+mod effects;
 pub mod glyphs;
+mod transitions;
 
+/// Draws a frame of `content` in the leftmost `width` columns of the buffer. Anything
+/// drawn to the right of them is meant to be clipped by the caller.
 pub fn render(
     content: &Content,
     rendered_glyphs: &RenderedGlyphs,
+    width: usize,
     duration: Option<Duration>,
     time_elapsed: Duration,
 ) -> Box<ScreenBuffer> {
-    let offset = animations::get_global_offset(
+    let mut offset = animations::get_global_offset(
         &content.animation,
         content.align,
-        rendered_glyphs.width,
+        rendered_glyphs,
+        width,
         duration,
         time_elapsed,
     );
 
-    let pixel = content.color.to_pixel();
+    let typewriter = animations::get_typewriter_state(
+        &content.animation,
+        rendered_glyphs,
+        width,
+        &offset,
+        time_elapsed,
+    );
+
+    if let Some(typewriter) = &typewriter {
+        offset.x -= typewriter.scroll;
+    }
 
     let mut buffer = Box::new(ScreenBuffer([[Pixel::default(); 80]; 7]));
 
-    for rendered_glyph in &rendered_glyphs.glyphs {
-        let glyph = rendered_glyph.glyph;
+    if !effects::is_visible(&content.effects, time_elapsed) {
+        return buffer;
+    }
+
+    let background = match (content.background, content.inverted) {
+        (Some(background), _) => Some(background),
+        (None, true) => Some(content.color),
+        (None, false) => None,
+    };
+
+    if let Some(background) = background {
+        let pixel = background.to_pixel_with_depth(COLOR_DEPTH);
+        buffer.iter_mut().for_each(|row| row.fill(pixel));
+    }
+
+    let copy_offsets =
+        animations::get_copy_offsets(&content.animation, rendered_glyphs.width, width, &offset);
+
+    let mode = if content.inverted {
+        // a solid band of the text's color behind each copy, gaps between glyphs
+        // included, for the glyphs to be cut out of
+        let pixel = effects::get_color(&content.effects, content.color, 0, time_elapsed)
+            .to_pixel_with_depth(COLOR_DEPTH);
+        let band_width = match &typewriter {
+            Some(typewriter) => typewriter.typed_width.min(rendered_glyphs.width),
+            None => rendered_glyphs.width,
+        };
 
-        let start_col = rendered_glyph.x_offset as i32 + offset.x;
-        let start_row = offset.y;
+        for x in &copy_offsets {
+            for row in 0..ScreenBuffer::HEIGHT as i32 {
+                for col in 0..band_width as i32 {
+                    buffer.set_if_in_bounds(offset.y + row, x + col, pixel);
+                }
+            }
+        }
 
-        glyph.copy_to_buffer(&mut buffer, pixel, start_col, start_row);
+        GlyphMode::Inverted
+    } else {
+        GlyphMode::Normal
+    };
+
+    for x in copy_offsets {
+        for (index, rendered_glyph) in rendered_glyphs.glyphs.iter().enumerate() {
+            if let Some(typewriter) = &typewriter {
+                if rendered_glyph.x_offset >= typewriter.typed_width {
+                    break;
+                }
+            }
+
+            let glyph = rendered_glyph.glyph;
+            let color = if rendered_glyph.from_bitmap {
+                rendered_glyph.color
+            } else {
+                effects::get_color(&content.effects, rendered_glyph.color, index, time_elapsed)
+            };
+
+            let start_col = rendered_glyph.x_offset as i32 + x;
+            let start_row = offset.y;
+
+            glyph.copy_to_buffer(
+                &mut buffer,
+                color.to_pixel_with_depth(COLOR_DEPTH),
+                mode,
+                start_col,
+                start_row,
+            );
+        }
+    }
+
+    if let Some(TypewriterState {
+        typed_width,
+        cursor: Some(cursor),
+        ..
+    }) = typewriter
+    {
+        let color = effects::get_color(
+            &content.effects,
+            content.color,
+            rendered_glyphs.glyphs.len(),
+            time_elapsed,
+        );
+
+        cursor.copy_to_buffer(
+            &mut buffer,
+            color.to_pixel_with_depth(COLOR_DEPTH),
+            mode,
+            typed_width as i32 + offset.x,
+            offset.y,
+        );
     }
 
     buffer
 }
+
+/// Draws a frame of `transition` from `outgoing` to `incoming`, both `width` columns wide
+pub fn render_transition(
+    outgoing: &ScreenBuffer,
+    incoming: &ScreenBuffer,
+    transition: &Transition,
+    width: usize,
+    time_elapsed: Duration,
+) -> Box<ScreenBuffer> {
+    let duration = transition.duration();
+    let progress = if duration.is_zero() {
+        1.0
+    } else {
+        time_elapsed.div_duration_f32(duration).min(1.0)
+    };
+
+    transitions::composite(outgoing, incoming, transition, width, progress)
+}
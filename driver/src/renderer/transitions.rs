@@ -0,0 +1,94 @@
+use prolite::{
+    api::{Transition, TransitionDirection},
+    Pixel, ScreenBuffer,
+};
+
+/// Combines the last frame of the outgoing content with the first frame of the
+/// incoming content, `progress` of the way (0 to 1) through the transition. Only the
+/// leftmost `width` columns are drawn.
+pub fn composite(
+    outgoing: &ScreenBuffer,
+    incoming: &ScreenBuffer,
+    transition: &Transition,
+    width: usize,
+    progress: f32,
+) -> Box<ScreenBuffer> {
+    let width = width as i32;
+    let height = ScreenBuffer::HEIGHT as i32;
+
+    let mut buffer = Box::new(ScreenBuffer::new());
+
+    for row in 0..height {
+        for col in 0..width {
+            let pixel = match transition {
+                Transition::Cut => incoming[row as usize][col as usize],
+                Transition::Wipe { direction, .. } => {
+                    let is_incoming = match direction {
+                        TransitionDirection::Left => col >= moved(width, progress, true),
+                        TransitionDirection::Right => col < moved(width, progress, false),
+                        TransitionDirection::Up => row >= moved(height, progress, true),
+                        TransitionDirection::Down => row < moved(height, progress, false),
+                    };
+
+                    if is_incoming {
+                        incoming[row as usize][col as usize]
+                    } else {
+                        outgoing[row as usize][col as usize]
+                    }
+                }
+                Transition::Push { direction, .. } => {
+                    // where this pixel was in the outgoing frame; past the edge of
+                    // the outgoing frame the incoming one follows right behind
+                    let (outgoing_row, outgoing_col) = match direction {
+                        TransitionDirection::Left => (row, col + moved(width, progress, false)),
+                        TransitionDirection::Right => (row, col - moved(width, progress, false)),
+                        TransitionDirection::Up => (row + moved(height, progress, false), col),
+                        TransitionDirection::Down => (row - moved(height, progress, false), col),
+                    };
+
+                    if (0..height).contains(&outgoing_row) && (0..width).contains(&outgoing_col) {
+                        outgoing[outgoing_row as usize][outgoing_col as usize]
+                    } else {
+                        incoming[outgoing_row.rem_euclid(height) as usize]
+                            [outgoing_col.rem_euclid(width) as usize]
+                    }
+                }
+                Transition::Dissolve { .. } => {
+                    if dissolve_threshold(row, col) < progress {
+                        incoming[row as usize][col as usize]
+                    } else {
+                        outgoing[row as usize][col as usize]
+                    }
+                }
+                Transition::Blank { .. } => Pixel::default(),
+            };
+
+            buffer[row as usize][col as usize] = pixel;
+        }
+    }
+
+    buffer
+}
+
+/// How far an edge has moved across `length` pixels, counted from the far side if
+/// `from_end` is set
+fn moved(length: i32, progress: f32, from_end: bool) -> i32 {
+    let moved = (length as f32 * progress).round() as i32;
+
+    if from_end {
+        length - moved
+    } else {
+        moved
+    }
+}
+
+/// A fixed pseudo-random value between 0 and 1 for each pixel, so each one switches
+/// over once and the order looks random
+fn dissolve_threshold(row: i32, col: i32) -> f32 {
+    let mut hash = (row as u32).wrapping_mul(0x9e37_79b9) ^ (col as u32).wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7feb_352d);
+    hash ^= hash >> 15;
+
+    (hash % 1024) as f32 / 1024.0
+}
@@ -0,0 +1,312 @@
+use std::time::Instant;
+
+use log::info;
+use prolite::{
+    api::{ContentGroup, CurrentStatus, ResumeMode, ZoneLayout, ZoneStatus},
+    template::TemplateContext,
+    ScreenBuffer,
+};
+
+use crate::{
+    queue::ContentQueue,
+    renderer::{
+        current_content::{ContentState, CurrentContent},
+        UnknownGlyphBehavior,
+    },
+};
+
+/// A part of the screen with everything that decides what it shows: the content
+/// being shown, the queue behind it, and any content that has been paused by an
+/// interrupt.
+pub struct Zone {
+    layout: ZoneLayout,
+    current_content: Option<CurrentContent>,
+    /// `None` for content from the queue, which any interrupt preempts
+    current_priority: Option<u8>,
+    content_queue: ContentQueue,
+    /// Paused content, most recently interrupted last
+    interrupted: Vec<InterruptedContent>,
+    /// Interrupts waiting for a higher priority interrupt to finish
+    pending_interrupts: Vec<PendingInterrupt>,
+    behavior: UnknownGlyphBehavior,
+    force_render: bool,
+    /// The last frame rendered in this zone, starting at column 0 of the buffer
+    frame: Box<ScreenBuffer>,
+}
+
+struct InterruptedContent {
+    content: CurrentContent,
+    priority: Option<u8>,
+    resume: ResumeMode,
+}
+
+struct PendingInterrupt {
+    content: ContentGroup,
+    priority: u8,
+}
+
+impl Zone {
+    pub fn new(layout: ZoneLayout, behavior: UnknownGlyphBehavior) -> Self {
+        Self {
+            layout,
+            current_content: None,
+            current_priority: None,
+            content_queue: ContentQueue::new(),
+            interrupted: vec![],
+            pending_interrupts: vec![],
+            behavior,
+            force_render: false,
+            frame: Box::new(ScreenBuffer::new()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.layout.name
+    }
+
+    pub fn add_to_queue(&mut self, content: ContentGroup) {
+        self.content_queue.push_back(content);
+    }
+
+    pub fn show_now(&mut self, content: ContentGroup) {
+        self.clear();
+        self.show(content, None);
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<(), String> {
+        let mut removed = self.content_queue.remove(id);
+
+        if self.is_current(id) {
+            self.current_content = None;
+            self.current_priority = None;
+            removed += 1;
+        }
+
+        let count = self.interrupted.len() + self.pending_interrupts.len();
+        self.interrupted.retain(|i| i.content.id() != Some(id));
+        self.pending_interrupts
+            .retain(|i| i.content.id.as_deref() != Some(id));
+        removed += count - self.interrupted.len() - self.pending_interrupts.len();
+
+        if removed == 0 {
+            Err(format!("no content with id {} to remove", id))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Looks for the group in what is showing, then in interrupted content (most
+    /// recently interrupted first), then in pending interrupts, then in the queue
+    pub fn replace(&mut self, id: &str, content: ContentGroup) -> Result<(), String> {
+        if self.is_current(id) {
+            self.show(content, self.current_priority);
+            return Ok(());
+        }
+
+        if let Some(interrupted) = self
+            .interrupted
+            .iter_mut()
+            .rev()
+            .find(|i| i.content.id() == Some(id))
+        {
+            // picked up from the beginning once it resumes
+            interrupted.content = CurrentContent::new(content, self.behavior, self.layout.width);
+            return Ok(());
+        }
+
+        if let Some(pending) = self
+            .pending_interrupts
+            .iter_mut()
+            .find(|i| i.content.id.as_deref() == Some(id))
+        {
+            pending.content = content;
+            return Ok(());
+        }
+
+        self.content_queue
+            .replace(id, content)
+            .map_err(|_| format!("no content with id {} to replace", id))
+    }
+
+    pub fn move_to(&mut self, id: &str, index: usize) -> Result<(), String> {
+        if self.content_queue.move_to(id, index) {
+            Ok(())
+        } else {
+            Err(format!("no queued content with id {} to move", id))
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, content: ContentGroup) {
+        self.content_queue.insert(index, content);
+    }
+
+    pub fn interrupt(
+        &mut self,
+        content: ContentGroup,
+        priority: u8,
+        resume: ResumeMode,
+        now: Instant,
+    ) {
+        let showing_priority = match self.current_content {
+            Some(_) => self.current_priority,
+            None => self.interrupted.last().and_then(|i| i.priority),
+        };
+
+        if Some(priority) < showing_priority {
+            info!("[render] interrupt is waiting for a higher priority interrupt");
+            self.pending_interrupts
+                .push(PendingInterrupt { content, priority });
+            return;
+        }
+
+        if let Some(mut interrupted) = self.current_content.take() {
+            interrupted.pause(now);
+            self.interrupted.push(InterruptedContent {
+                content: interrupted,
+                priority: self.current_priority,
+                resume,
+            });
+        }
+
+        self.show(content, Some(priority));
+    }
+
+    pub fn status(&self) -> ZoneStatus {
+        ZoneStatus {
+            layout: self.layout.clone(),
+            queue_length: self.content_queue.len(),
+            current: self.current_content.as_ref().map(|cc| CurrentStatus {
+                id: cc.id().map(str::to_owned),
+                step: cc.step_index(),
+                steps: cc.step_count(),
+                text: cc.content().text.clone(),
+                priority: self.current_priority,
+            }),
+            interrupted: self.interrupted.len(),
+        }
+    }
+
+    /// Advances the current content, and returns whether the zone's frame changed
+    pub fn render_frame(&mut self, now: Instant, context: &TemplateContext) -> bool {
+        if self.current_content.is_none() {
+            self.show_next(now);
+        }
+
+        let Some(cc) = self.current_content.as_mut() else {
+            return false;
+        };
+
+        let should_render_current_frame;
+        let should_replace_current_content;
+
+        let u = cc.update(now, context);
+        match u {
+            ContentState::StepStarted | ContentState::Changed => {
+                should_render_current_frame = true;
+                should_replace_current_content = false;
+            }
+            ContentState::StepIncomplete => {
+                should_render_current_frame = cc.is_animated() || self.force_render;
+                should_replace_current_content = false;
+            }
+            ContentState::Finished => {
+                should_render_current_frame = cc.is_animated() || self.force_render;
+                should_replace_current_content = true;
+            }
+        }
+
+        self.force_render = false;
+
+        if should_render_current_frame {
+            self.frame = cc.render(now);
+        }
+
+        if should_replace_current_content {
+            info!(
+                "[render] finished rendering previous content in zone {}",
+                self.layout.name
+            );
+            self.current_content = None;
+            self.current_priority = None;
+        }
+
+        should_render_current_frame
+    }
+
+    /// Draws the last rendered frame into the zone's columns of `buffer`, clipping
+    /// anything that was rendered past the zone's width
+    pub fn copy_to_buffer(&self, buffer: &mut ScreenBuffer) {
+        let ZoneLayout { x, width, .. } = self.layout;
+
+        for (row, frame_row) in buffer.iter_mut().zip(self.frame.iter()) {
+            row[x..x + width].copy_from_slice(&frame_row[..width]);
+        }
+    }
+
+    fn show(&mut self, content: ContentGroup, priority: Option<u8>) {
+        self.current_content = Some(CurrentContent::new(
+            content,
+            self.behavior,
+            self.layout.width,
+        ));
+        self.current_priority = priority;
+    }
+
+    /// Picks what to show after the current content finished: a pending interrupt,
+    /// then interrupted content, then the queue
+    fn show_next(&mut self, now: Instant) {
+        let interrupted_priority = self.interrupted.last().and_then(|i| i.priority);
+
+        // highest priority first, oldest first among equal priorities
+        let next_interrupt = self
+            .pending_interrupts
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, pending)| pending.priority)
+            .map(|(i, _)| i);
+
+        if let Some(i) = next_interrupt {
+            if Some(self.pending_interrupts[i].priority) >= interrupted_priority {
+                let pending = self.pending_interrupts.remove(i);
+                self.show(pending.content, Some(pending.priority));
+                return;
+            }
+        }
+
+        if let Some(InterruptedContent {
+            mut content,
+            priority,
+            resume,
+        }) = self.interrupted.pop()
+        {
+            info!("[render] resuming interrupted content");
+
+            match resume {
+                ResumeMode::Continue => content.resume(now),
+                ResumeMode::Restart => content.restart(now),
+            }
+
+            self.current_content = Some(content);
+            self.current_priority = priority;
+            self.force_render = true;
+            return;
+        }
+
+        if let Some(next_content) = self.content_queue.pop_front() {
+            self.show(next_content, None);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.content_queue.clear();
+        self.interrupted.clear();
+        self.pending_interrupts.clear();
+        self.current_content = None;
+        self.current_priority = None;
+    }
+
+    fn is_current(&self, id: &str) -> bool {
+        self.current_content.as_ref().and_then(|cc| cc.id()) == Some(id)
+    }
+}
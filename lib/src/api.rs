@@ -1,33 +1,436 @@
-use crate::{Level, Pixel};
+use crate::bitmap::{Bitmap, Sprite};
+use crate::markup::{self, MarkupError};
+use crate::template::Placeholder;
+use crate::{ColorDepth, Level, Pixel};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use serde_with::DurationSecondsWithFrac;
 use std::time::Duration;
 
+#[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "method", rename_all = "snake_case")]
 pub enum Command {
-    AddToQueue { content: ContentGroup },
-    ShowNow { content: ContentGroup },
-    Clear,
+    AddToQueue {
+        content: ContentGroup,
+        #[serde(default)]
+        zone: Option<String>,
+    },
+    ShowNow {
+        content: ContentGroup,
+        #[serde(default)]
+        zone: Option<String>,
+    },
+    Clear {
+        #[serde(default)]
+        zone: Option<String>,
+    },
+    /// Removes every group with this id, including the one currently showing
+    Remove {
+        id: String,
+        #[serde(default)]
+        zone: Option<String>,
+    },
+    /// Swaps out the first group with this id without changing its place in the queue.
+    /// If that group is showing or was paused by an interrupt, the new content starts
+    /// from the beginning. Interrupts that are waiting their turn can be replaced too.
+    Replace {
+        id: String,
+        content: ContentGroup,
+        #[serde(default)]
+        zone: Option<String>,
+    },
+    /// Moves the first queued group with this id to `index` (0 is up next)
+    Move {
+        id: String,
+        index: usize,
+        #[serde(default)]
+        zone: Option<String>,
+    },
+    /// Inserts a group at `index` in the queue (0 is up next), or at the end if
+    /// the queue is shorter than that
+    Insert {
+        index: usize,
+        content: ContentGroup,
+        #[serde(default)]
+        zone: Option<String>,
+    },
+    /// Shows a group right away without touching the queue. Whatever was showing is
+    /// paused and picked up again once the interrupt is over. An interrupt only
+    /// preempts another interrupt of the same or lower priority; otherwise it waits
+    /// for the higher priority one to finish.
+    Interrupt {
+        content: ContentGroup,
+        #[serde(default)]
+        priority: u8,
+        #[serde(default)]
+        resume: ResumeMode,
+        #[serde(default)]
+        zone: Option<String>,
+    },
+    /// Asks the driver for a `Response::Status`
+    GetStatus,
+    /// Sets the value shown by `{counter:name}` placeholders
+    SetCounter { name: String, value: i64 },
+    /// Sets the driver's wall clock, which otherwise only knows the time since boot
+    SetTime {
+        /// Time since the unix epoch (UTC)
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        epoch: Duration,
+        #[serde(default)]
+        utc_offset_minutes: i32,
+    },
+    /// Dims the whole display, from 0 (off) to 255 (full brightness, the default)
+    SetBrightness { level: u8 },
+    /// Splits the screen into zones that each have their own queue, interrupts and
+    /// content. Every command above that deals with content takes an optional `zone`
+    /// name, and goes to the first zone without one. Until this is sent, there is a
+    /// single zone named `main` covering the whole screen.
+    ///
+    /// Replaces every zone along with everything queued in it. Zones can't overlap,
+    /// and columns outside of every zone stay dark.
+    SetZones { zones: Vec<ZoneLayout> },
+}
+
+/// A part of the screen, see `Command::SetZones`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZoneLayout {
+    pub name: String,
+    /// The leftmost column
+    pub x: usize,
+    pub width: usize,
+}
+
+impl ZoneLayout {
+    pub const DEFAULT_NAME: &'static str = "main";
+}
+
+impl Default for ZoneLayout {
+    fn default() -> Self {
+        Self {
+            name: Self::DEFAULT_NAME.to_owned(),
+            x: 0,
+            width: crate::ScreenBuffer::WIDTH,
+        }
+    }
+}
+
+/// Sent back by the driver for every command it receives
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum Response {
+    Accepted,
+    Rejected { error: String },
+    Status { status: DriverStatus },
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DriverStatus {
+    /// Same as in the first entry of `zones`
+    pub queue_length: usize,
+    pub current: Option<CurrentStatus>,
+    /// Number of groups paused by an interrupt
+    pub interrupted: usize,
+    pub zones: Vec<ZoneStatus>,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub uptime: Duration,
+    /// Time since the unix epoch (UTC), if the time has been set
+    #[serde_as(as = "Option<DurationSecondsWithFrac<f64>>")]
+    pub time: Option<Duration>,
+    pub utc_offset_minutes: i32,
+    /// How much slower the driver's clock runs than the time it was given (negative
+    /// if it runs fast), measured between the last two `SetTime` commands at least a
+    /// minute apart. `None` before that, and after a `SetTime` that moved the time by
+    /// more than any clock drifts.
+    pub clock_drift_ppm: Option<f64>,
+    pub brightness: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneStatus {
+    pub layout: ZoneLayout,
+    pub queue_length: usize,
+    pub current: Option<CurrentStatus>,
+    /// Number of groups paused by an interrupt
+    pub interrupted: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CurrentStatus {
+    pub id: Option<String>,
+    pub step: usize,
+    pub steps: usize,
+    pub text: Text,
+    /// `None` unless this is an interrupt
+    pub priority: Option<u8>,
+}
+
+/// What to do with the content that was interrupted once the interrupt is over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ResumeMode {
+    /// Continue at the same point within the same step
+    #[default]
+    Continue,
+    /// Start the group over from its first step
+    Restart,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ContentGroup {
+    /// Lets later commands refer to this group
+    #[serde(default)]
+    pub id: Option<String>,
     pub contents: Vec<Content>,
     #[serde(default)]
     pub repeat: Repeat,
+    /// How to get from one content to the next, unless the next content has its own
+    #[serde(default)]
+    pub transition: Transition,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Content {
-    pub text: String,
+    pub text: Text,
     #[serde(default)]
     pub color: Color,
     #[serde(default)]
     pub animation: Animation,
     #[serde(default)]
     pub align: Alignment,
+    /// Interpret the text as markup (see `prolite::markup`); off by default so
+    /// literal braces in existing text are left alone
+    #[serde(default)]
+    pub markup: bool,
+    /// How to get here from the previous content in the group, overriding the
+    /// group's transition
+    #[serde(default)]
+    pub transition: Option<Transition>,
+    /// Applied on top of the animation, in order
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+    /// Fills the screen behind the text
+    #[serde(default)]
+    pub background: Option<Color>,
+    /// Cuts the text out of a block of its color instead of lighting it. Without a
+    /// `background`, the rest of the screen is filled with `color`.
+    #[serde(default)]
+    pub inverted: bool,
+}
+
+impl Content {
+    pub fn spans(&self) -> Result<Vec<Span>, MarkupError> {
+        if !self.markup {
+            return Ok(self.text.to_spans());
+        }
+
+        let mut spans = vec![];
+
+        for span in self.text.to_spans() {
+            match span {
+                Span::Text { text, color } => spans.extend(markup::parse(&text, color)?),
+                span => spans.push(span),
+            }
+        }
+
+        Ok(spans)
+    }
+}
+
+/// Either a plain string drawn in the content's `color`, or a list of spans that
+/// can each override that color, e.g. `[{"text": "UP", "color": "green"}, {"text": " DOWN", "color": "red"}]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Text {
+    Plain(String),
+    Spans(Vec<Span>),
+}
+
+impl Text {
+    pub fn to_spans(&self) -> Vec<Span> {
+        match self {
+            Text::Plain(text) => vec![Span::Text {
+                text: text.clone(),
+                color: None,
+            }],
+            Text::Spans(spans) => spans.clone(),
+        }
+    }
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Text::Plain(String::new())
+    }
+}
+
+impl From<String> for Text {
+    fn from(text: String) -> Self {
+        Text::Plain(text)
+    }
+}
+
+impl From<&str> for Text {
+    fn from(text: &str) -> Self {
+        Text::Plain(text.to_owned())
+    }
+}
+
+/// A run of text, a named icon such as `{"icon": "heart"}`, a placeholder such
+/// as `{"placeholder": {"type": "time", "format": "%H:%M"}}`, or a bitmap or sprite
+/// (see `prolite::bitmap`). Without a `color`, a span falls back to the color of the
+/// containing `Content`; bitmaps and sprites always keep their own colors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Span {
+    Text {
+        text: String,
+        #[serde(default)]
+        color: Option<Color>,
+    },
+    Icon {
+        icon: String,
+        #[serde(default)]
+        color: Option<Color>,
+    },
+    Placeholder {
+        placeholder: Placeholder,
+        #[serde(default)]
+        color: Option<Color>,
+    },
+    Bitmap {
+        bitmap: Bitmap,
+    },
+    /// Animates while its content is shown. A sprite that doesn't loop forever ends
+    /// a step whose duration is `forever` once it has played.
+    Sprite {
+        sprite: Sprite,
+    },
+}
+
+impl Span {
+    pub fn color(&self) -> Option<Color> {
+        match self {
+            Span::Text { color, .. }
+            | Span::Icon { color, .. }
+            | Span::Placeholder { color, .. } => *color,
+            Span::Bitmap { .. } | Span::Sprite { .. } => None,
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Effect {
+    /// Turns the whole content on and off
+    Blink {
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        #[serde(default = "default_blink_period")]
+        period: Duration,
+        /// The part of each period the content is on, from 0 to 1
+        #[serde(default = "default_duty_cycle")]
+        duty_cycle: f64,
+    },
+    /// Switches the whole content from one color to the next. Like `Rainbow`, this
+    /// leaves bitmaps and sprites in their own colors.
+    Alternate {
+        #[serde(default = "default_alternate_colors")]
+        colors: Vec<Color>,
+        /// How long each color is shown
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        #[serde(default = "default_effect_interval")]
+        interval: Duration,
+    },
+    /// Gives each character the next color after its neighbour, and moves the
+    /// colors along over time
+    Rainbow {
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        #[serde(default = "default_effect_interval")]
+        interval: Duration,
+    },
+}
+
+fn default_blink_period() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_duty_cycle() -> f64 {
+    0.5
+}
+
+fn default_alternate_colors() -> Vec<Color> {
+    vec![Color::Red, Color::Green]
+}
+
+fn default_effect_interval() -> Duration {
+    Duration::from_millis(500)
+}
+
+/// Played between two contents of a group. The outgoing content stays on its last
+/// frame and the incoming content on its first until the transition is over.
+#[serde_as]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Transition {
+    /// Switches right away
+    #[default]
+    Cut,
+    /// Reveals the incoming content with an edge moving in `direction`
+    Wipe {
+        #[serde(default)]
+        direction: TransitionDirection,
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        #[serde(default = "default_transition_duration")]
+        duration: Duration,
+    },
+    /// Moves both contents in `direction`, the incoming one right behind the outgoing one
+    Push {
+        #[serde(default)]
+        direction: TransitionDirection,
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        #[serde(default = "default_transition_duration")]
+        duration: Duration,
+    },
+    /// Swaps pixels over to the incoming content in random order
+    Dissolve {
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        #[serde(default = "default_transition_duration")]
+        duration: Duration,
+    },
+    /// Leaves the screen empty in between
+    Blank {
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        #[serde(default = "default_transition_duration")]
+        duration: Duration,
+    },
+}
+
+fn default_transition_duration() -> Duration {
+    Duration::from_millis(500)
+}
+
+impl Transition {
+    pub fn duration(&self) -> Duration {
+        match self {
+            Transition::Cut => Duration::ZERO,
+            Transition::Wipe { duration, .. }
+            | Transition::Push { duration, .. }
+            | Transition::Dissolve { duration }
+            | Transition::Blank { duration } => *duration,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionDirection {
+    #[default]
+    Left,
+    Right,
+    Up,
+    Down,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -44,31 +447,128 @@ impl Default for Repeat {
     }
 }
 
+/// A named color, or a `#rgb` or `#rrggbb` color, which is shown as the closest pixel
+/// the display can light up (see `Color::to_pixel`)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+#[serde(try_from = "String", into = "String")]
 pub enum Color {
     Red,
     Green,
     Orange,
+    Yellow,
+    Amber,
+    Lime,
+    DimRed,
+    DimGreen,
+    DimOrange,
+    Rgb(u8, u8, u8),
 }
 
 impl Color {
+    const NAMED: [(&'static str, Color); 9] = [
+        ("red", Color::Red),
+        ("green", Color::Green),
+        ("orange", Color::Orange),
+        ("yellow", Color::Yellow),
+        ("amber", Color::Amber),
+        ("lime", Color::Lime),
+        ("dim_red", Color::DimRed),
+        ("dim_green", Color::DimGreen),
+        ("dim_orange", Color::DimOrange),
+    ];
+
+    /// A name such as `red` or `dim_green`, or a hex color such as `#ff8800` or `#f80`.
+    /// The display has no blue, so hex colors whose red and green are too dark to
+    /// light up anything are rejected unless they are black (`#000`).
+    pub fn from_name(name: &str) -> Option<Color> {
+        if let Some(hex) = name.strip_prefix('#') {
+            let digits = hex
+                .chars()
+                .map(|c| c.to_digit(16).map(|d| d as u8))
+                .collect::<Option<Vec<_>>>()?;
+
+            let color = match digits[..] {
+                [r, g, b] => Color::Rgb(r * 0x11, g * 0x11, b * 0x11),
+                [r1, r2, g1, g2, b1, b2] => Color::Rgb(r1 << 4 | r2, g1 << 4 | g2, b1 << 4 | b2),
+                _ => return None,
+            };
+
+            return match color {
+                Color::Rgb(_, _, b) if b != 0 && color.to_pixel() == Pixel::OFF => None,
+                color => Some(color),
+            };
+        }
+
+        Self::NAMED
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, color)| *color)
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+            color => Self::NAMED
+                .iter()
+                .find(|(_, c)| *c == color)
+                .map(|(n, _)| (*n).to_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The closest pixel on a board that can draw every level. Named colors are tuned
+    /// to how the panel looks, e.g. fully lit red and green look orange rather than
+    /// yellow. For hex colors, red and green map to the channel levels directly and
+    /// blue is ignored, since the display has no blue.
     pub fn to_pixel(self) -> Pixel {
         match self {
-            Color::Red => Pixel {
-                red: Level::On,
-                green: Level::Off,
-            },
-            Color::Green => Pixel {
-                red: Level::Off,
-                green: Level::On,
-            },
-            Color::Orange => Pixel {
-                red: Level::On,
-                green: Level::On,
-            },
+            Color::Red => Pixel::RED,
+            Color::Green => Pixel::GREEN,
+            Color::Orange => Pixel::ORANGE,
+            Color::Yellow => Pixel::YELLOW,
+            Color::Amber => Pixel::AMBER,
+            Color::Lime => Pixel::LIME,
+            Color::DimRed => Pixel::new(Level::Dim, Level::Off),
+            Color::DimGreen => Pixel::new(Level::Off, Level::Dim),
+            Color::DimOrange => Pixel::new(Level::Dim, Level::Dim),
+            Color::Rgb(r, g, _) => {
+                let level = |c: u8| {
+                    let max = (1u32 << Level::BITS) - 1;
+                    Level::from_intensity(((c as u32 * max + 127) / 255) as u8)
+                };
+
+                Pixel::new(level(r), level(g))
+            }
         }
     }
+
+    /// The closest pixel a board with the given `depth` can show
+    pub fn to_pixel_with_depth(self, depth: ColorDepth) -> Pixel {
+        self.to_pixel().with_depth(depth)
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Color::from_name(&name).ok_or_else(|| {
+            if name.starts_with('#') {
+                format!(
+                    "'{}' is not a #rgb or #rrggbb color with some red or green",
+                    name
+                )
+            } else {
+                format!("unknown color '{}'", name)
+            }
+        })
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.name()
+    }
 }
 
 impl Default for Color {
@@ -111,7 +611,8 @@ impl Default for ScrollPosition {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Animation {
     None {
@@ -125,15 +626,135 @@ pub enum Animation {
         direction: SlideDirection,
         #[serde(default)]
         speed: SlideSpeed,
+        #[serde(default)]
+        easing: Easing,
+        /// How long to stay at the aligned position: after sliding in, before sliding
+        /// out, or in between for `in_out`. The speed only covers the movement.
+        #[serde(default)]
+        hold: Hold,
     },
+    /// Scrolls text that doesn't fit from its beginning to its end. A speed given as
+    /// a duration is the time for each pass.
     SlideInBounds {
         #[serde(default)]
         direction: SlideInBoundsDirection,
         #[serde(default)]
         speed: SlideSpeed,
+        #[serde(default)]
+        easing: Easing,
+        /// How long to wait before scrolling
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        #[serde(default)]
+        pause_at_start: Duration,
+        /// How long to wait after scrolling, and before scrolling back with `ping_pong`
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        #[serde(default)]
+        pause_at_end: Duration,
+        /// Scroll back to the beginning after reaching the end
+        #[serde(default)]
+        ping_pong: bool,
+        /// Keeps the content on screen at least this long, which matters mostly for
+        /// text that already fits and so would otherwise disappear right away
+        #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+        #[serde(default)]
+        min_duration: Duration,
+    },
+    /// Scrolls from right to left with copies of the text following each other, so
+    /// there is no empty screen between them. A speed given as a duration is the time
+    /// it takes for the next copy to arrive.
+    Ticker {
+        /// Columns between the end of one copy and the start of the next
+        #[serde(default = "default_ticker_gap")]
+        gap: usize,
+        #[serde(default)]
+        speed: SlideSpeed,
+        /// How many more copies to show after the first one (at most 65535)
+        #[serde(default = "default_ticker_repeat")]
+        repeat: Repeat,
+    },
+    /// Reveals the text one character at a time, scrolling along if it doesn't fit
+    Typewriter {
+        /// Characters per second
+        #[serde(default = "default_typewriter_cps")]
+        cps: usize,
+        /// Shown after the last revealed character, blinking once everything is typed
+        #[serde(default)]
+        cursor: Option<char>,
+        /// How long to keep showing the text once it's all typed, forever if left out
+        #[serde(default)]
+        hold: Option<Hold>,
+    },
+    /// Moves the content along a path, in order of time. Before the first keyframe
+    /// and after the last one, the content stays where that keyframe puts it.
+    Keyframes { keyframes: Vec<Keyframe> },
+    /// Plays one animation after the other. Phases that set their own position
+    /// (`SlideInBounds`, `Ticker`, `Typewriter`, and `Auto` for text that doesn't fit)
+    /// stay where they are. Every other phase is moved to join up with them: it
+    /// starts where the previous phase ended, or, before the first phase that sets
+    /// its own position, ends where that phase starts. Tickers and typewriters only
+    /// draw their copies or reveal the text at the top level.
+    Sequence { phases: Vec<Animation> },
+    /// Shows the text without moving it if it fits on the screen, and scrolls it
+    /// otherwise
+    Auto {
+        #[serde(default)]
+        speed: SlideSpeed,
+        /// How long to show text that fits, and how long to wait before and after
+        /// scrolling with `slide_in_bounds`
+        #[serde(default = "default_auto_hold")]
+        hold: Hold,
+        #[serde(default)]
+        overflow: Overflow,
+    },
+}
+
+/// A position relative to where the content's alignment puts it, e.g. `y: 7` is
+/// just below the screen
+#[serde_as]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// Time since the content started
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub time: Duration,
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    /// How to move from the previous keyframe to this one
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+/// How `Animation::Auto` scrolls text that doesn't fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Overflow {
+    /// From beginning to end, once
+    #[default]
+    SlideInBounds,
+    /// Through the screen from right to left, once
+    Ticker {
+        #[serde(default = "default_ticker_gap")]
+        gap: usize,
     },
 }
 
+fn default_auto_hold() -> Hold {
+    Hold::Duration(Duration::from_secs(2))
+}
+
+fn default_typewriter_cps() -> usize {
+    10
+}
+
+fn default_ticker_gap() -> usize {
+    16
+}
+
+fn default_ticker_repeat() -> Repeat {
+    Repeat::Forever
+}
+
 impl Default for Animation {
     fn default() -> Self {
         Animation::None {
@@ -184,6 +805,24 @@ impl Default for SlideInBoundsDirection {
     }
 }
 
+/// How a slide moves over its duration. With anything but `Linear`, a speed in dps
+/// is the average speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Starts slow and speeds up
+    EaseIn,
+    /// Starts fast and slows down
+    EaseOut,
+    EaseInOut,
+    /// Bounces back a few times before settling at the end
+    Bounce,
+    /// Overshoots the end and springs back
+    Elastic,
+}
+
 #[serde_as]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -203,11 +842,220 @@ impl Default for SlideSpeed {
 #[serde(rename_all = "snake_case")]
 pub enum ContentDuration {
     Duration(#[serde_as(as = "DurationSecondsWithFrac<f64>")] Duration),
+    /// Long enough to read the text
+    Reading(ReadingTime),
     Forever,
 }
 
+/// A pause that is either a number of seconds, or long enough to read the text,
+/// e.g. `2.5` or `{"reading": {"per_word": 0.4}}`
+#[serde_as]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Hold {
+    Duration(#[serde_as(as = "DurationSecondsWithFrac<f64>")] Duration),
+    Reading { reading: ReadingTime },
+}
+
+impl Default for Hold {
+    fn default() -> Self {
+        Hold::Duration(Duration::ZERO)
+    }
+}
+
+/// `base + per_character * characters + per_word * words`, kept between `min` and
+/// `max`. Whitespace doesn't count as a character, and icons count as a word.
+#[serde_as]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadingTime {
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    #[serde(default = "default_reading_base")]
+    pub base: Duration,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    #[serde(default)]
+    pub per_character: Duration,
+    /// 0.3s by default, or about 200 words per minute
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    #[serde(default = "default_reading_per_word")]
+    pub per_word: Duration,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    #[serde(default)]
+    pub min: Duration,
+    #[serde_as(as = "Option<DurationSecondsWithFrac<f64>>")]
+    #[serde(default)]
+    pub max: Option<Duration>,
+}
+
+fn default_reading_base() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_reading_per_word() -> Duration {
+    Duration::from_millis(300)
+}
+
+impl ReadingTime {
+    pub fn duration(&self, characters: usize, words: usize) -> Duration {
+        let count = |n: usize| u32::try_from(n).unwrap_or(u32::MAX);
+        let duration = self
+            .base
+            .saturating_add(self.per_character.saturating_mul(count(characters)))
+            .saturating_add(self.per_word.saturating_mul(count(words)));
+        let duration = duration.max(self.min);
+
+        match self.max {
+            Some(max) => duration.min(max),
+            None => duration,
+        }
+    }
+}
+
 impl Default for ContentDuration {
     fn default() -> Self {
         ContentDuration::Forever
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading_time(per_character: Duration, max: Option<Duration>) -> ReadingTime {
+        ReadingTime {
+            base: Duration::from_secs(1),
+            per_character,
+            per_word: Duration::from_millis(300),
+            min: Duration::from_secs(2),
+            max,
+        }
+    }
+
+    #[test]
+    fn reading_time_counts_characters_and_words() {
+        let reading = reading_time(Duration::from_millis(50), None);
+
+        assert_eq!(reading.duration(20, 4), Duration::from_millis(3200));
+        assert_eq!(reading.duration(1, 1), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn reading_time_is_capped() {
+        let reading = reading_time(Duration::from_millis(50), Some(Duration::from_secs(3)));
+
+        assert_eq!(reading.duration(100, 20), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn reading_time_saturates_instead_of_overflowing() {
+        let reading = reading_time(Duration::MAX, None);
+
+        assert_eq!(reading.duration(2, 1), Duration::MAX);
+        assert_eq!(
+            reading_time(Duration::ZERO, Some(Duration::from_secs(60))).duration(0, usize::MAX),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn named_colors() {
+        assert_eq!(Color::from_name("red"), Some(Color::Red));
+        assert_eq!(Color::from_name("dim_orange"), Some(Color::DimOrange));
+        assert_eq!(Color::from_name("blue"), None);
+        assert_eq!(Color::from_name("Red"), None);
+        assert_eq!(Color::from_name(""), None);
+    }
+
+    #[test]
+    fn long_hex_colors() {
+        assert_eq!(
+            Color::from_name("#ff8800"),
+            Some(Color::Rgb(0xff, 0x88, 0x00))
+        );
+        assert_eq!(
+            Color::from_name("#A0b1C2"),
+            Some(Color::Rgb(0xa0, 0xb1, 0xc2))
+        );
+    }
+
+    #[test]
+    fn short_hex_colors() {
+        assert_eq!(Color::from_name("#f80"), Some(Color::Rgb(0xff, 0x88, 0x00)));
+        assert_eq!(Color::from_name("#0Fa"), Some(Color::Rgb(0x00, 0xff, 0xaa)));
+    }
+
+    #[test]
+    fn invalid_hex_colors() {
+        for name in [
+            "#", "#f8", "#ff80", "#ff880", "#ff88000", "#ff880g", "#+f+f+f", "#ffé", "ff8800",
+        ] {
+            assert_eq!(Color::from_name(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for (name, color) in Color::NAMED {
+            assert_eq!(color.name(), name);
+            assert_eq!(Color::try_from(color.name()), Ok(color));
+        }
+
+        assert_eq!(
+            Color::from_name("#F80").map(Color::name),
+            Some("#ff8800".to_owned())
+        );
+        assert_eq!(String::from(Color::Rgb(1, 2, 3)), "#010203");
+    }
+
+    #[test]
+    fn unknown_color_error() {
+        assert_eq!(
+            Color::try_from("blue".to_owned()),
+            Err("unknown color 'blue'".to_owned())
+        );
+        assert!(Color::try_from("#12345".to_owned())
+            .unwrap_err()
+            .contains("is not a #rgb or #rrggbb color"));
+    }
+
+    #[test]
+    fn colors_that_only_light_blue_are_rejected() {
+        assert_eq!(Color::from_name("#0000ff"), None);
+        assert_eq!(Color::from_name("#00f"), None);
+        assert_eq!(Color::from_name("#1010ff"), None);
+        assert!(Color::try_from("#00f".to_owned())
+            .unwrap_err()
+            .contains("red or green"));
+
+        assert_eq!(Color::from_name("#000"), Some(Color::Rgb(0, 0, 0)));
+        assert_eq!(Color::from_name("#80f"), Some(Color::Rgb(0x88, 0, 0xff)));
+    }
+
+    #[test]
+    fn hex_colors_round_to_the_nearest_level() {
+        assert_eq!(Color::Rgb(0xff, 0x00, 0xff).to_pixel(), Pixel::RED);
+        assert_eq!(
+            Color::Rgb(0x80, 0x55, 0).to_pixel(),
+            Pixel::new(Level::Medium, Level::Dim)
+        );
+        assert_eq!(
+            Color::Rgb(0x2a, 0x2b, 0).to_pixel(),
+            Pixel::new(Level::Off, Level::Dim)
+        );
+    }
+
+    #[test]
+    fn on_off_keeps_lit_pixels_lit() {
+        let on_off = |color: Color| color.to_pixel_with_depth(ColorDepth::OnOff);
+
+        assert_eq!(on_off(Color::Yellow), Pixel::ORANGE);
+        assert_eq!(on_off(Color::Amber), Pixel::ORANGE);
+        assert_eq!(on_off(Color::Lime), Pixel::GREEN);
+        assert_eq!(on_off(Color::DimRed), Pixel::RED);
+        assert_eq!(on_off(Color::DimOrange), Pixel::ORANGE);
+        assert_eq!(on_off(Color::Rgb(0, 0, 0xff)), Pixel::OFF);
+        assert_eq!(
+            Color::Yellow.to_pixel_with_depth(ColorDepth::Levels),
+            Pixel::YELLOW
+        );
+    }
+}
@@ -0,0 +1,291 @@
+// Pixel art for content that doesn't fit in the glyph tables, such as logos and
+// small charts. Bitmaps are always as tall as the screen and as wide as they need to be.
+//
+// Each color channel is a hex string with two digits per column, where bit 0 is the
+// top row and bit 6 the bottom row. A pixel with both channels on is orange.
+//
+//   {"red": "7f4141417f", "green": "0000080000"}
+//
+// is a red box, 5 columns wide, with a green dot in the middle. A channel that is
+// left out is off everywhere.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationSecondsWithFrac};
+
+use crate::api::Repeat;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "EncodedBitmap", into = "EncodedBitmap")]
+pub struct Bitmap {
+    /// One byte per column
+    pub red: Vec<u8>,
+    /// One byte per column, same length as `red`
+    pub green: Vec<u8>,
+}
+
+impl Bitmap {
+    pub fn width(&self) -> usize {
+        self.red.len()
+    }
+}
+
+/// Bitmaps shown one after another, e.g. for spinners and other animated icons
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sprite {
+    pub frames: Vec<Bitmap>,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub frame_duration: Duration,
+    /// How often to play the frames again after the first time; loops forever by default
+    #[serde(default = "default_sprite_repeat")]
+    pub repeat: Repeat,
+}
+
+fn default_sprite_repeat() -> Repeat {
+    Repeat::Forever
+}
+
+impl Sprite {
+    /// `None` if the sprite loops forever, or for longer than a `Duration` can hold
+    pub fn duration(&self) -> Option<Duration> {
+        let loops = match self.repeat {
+            Repeat::None => 1,
+            Repeat::Times(n) => u32::try_from(n).ok().and_then(|n| n.checked_add(1))?,
+            Repeat::Forever => return None,
+        };
+
+        self.frame_duration
+            .checked_mul(u32::try_from(self.frames.len()).ok()?)?
+            .checked_mul(loops)
+    }
+
+    /// The frame to show `elapsed` after the sprite started; sprites that have
+    /// finished keep showing their last frame
+    pub fn frame_at(&self, elapsed: Duration) -> Option<&Bitmap> {
+        if self.frames.is_empty() || self.frame_duration.is_zero() {
+            return self.frames.first();
+        }
+
+        let index = (elapsed.as_nanos() / self.frame_duration.as_nanos()) as usize;
+
+        match self.duration() {
+            Some(duration) if elapsed >= duration => self.frames.last(),
+            _ => self.frames.get(index % self.frames.len()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncodedBitmap {
+    #[serde(default)]
+    red: String,
+    #[serde(default)]
+    green: String,
+}
+
+impl TryFrom<EncodedBitmap> for Bitmap {
+    type Error = String;
+
+    fn try_from(encoded: EncodedBitmap) -> Result<Self, Self::Error> {
+        let mut red = decode_hex(&encoded.red).map_err(|e| format!("red channel: {}", e))?;
+        let mut green = decode_hex(&encoded.green).map_err(|e| format!("green channel: {}", e))?;
+
+        if red.is_empty() {
+            red = vec![0; green.len()];
+        } else if green.is_empty() {
+            green = vec![0; red.len()];
+        } else if red.len() != green.len() {
+            return Err(format!(
+                "red channel is {} columns wide, but green channel is {}",
+                red.len(),
+                green.len()
+            ));
+        }
+
+        Ok(Self { red, green })
+    }
+}
+
+impl From<Bitmap> for EncodedBitmap {
+    fn from(bitmap: Bitmap) -> Self {
+        Self {
+            red: encode_hex(&bitmap.red),
+            green: encode_hex(&bitmap.green),
+        }
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    s.as_bytes()
+        .chunks(2)
+        .enumerate()
+        .map(|(i, byte)| {
+            if byte.len() != 2 {
+                return Err("expected two hex digits per column".to_owned());
+            }
+
+            // from_str_radix also takes a leading '+', which isn't a hex digit
+            if !byte.iter().all(u8::is_ascii_hexdigit) {
+                return Err(format!("invalid hex at position {}", i * 2));
+            }
+
+            std::str::from_utf8(byte)
+                .ok()
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| format!("invalid hex at position {}", i * 2))
+        })
+        .collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(red: &str, green: &str) -> Result<Bitmap, String> {
+        Bitmap::try_from(EncodedBitmap {
+            red: red.to_owned(),
+            green: green.to_owned(),
+        })
+    }
+
+    fn sprite(frames: usize, repeat: Repeat) -> Sprite {
+        Sprite {
+            frames: (0..frames as u8)
+                .map(|i| Bitmap {
+                    red: vec![i],
+                    green: vec![0],
+                })
+                .collect(),
+            frame_duration: Duration::from_millis(100),
+            repeat,
+        }
+    }
+
+    #[test]
+    fn decodes_both_channels() {
+        assert_eq!(
+            decode("7f4141417f", "0000080000"),
+            Ok(Bitmap {
+                red: vec![0x7f, 0x41, 0x41, 0x41, 0x7f],
+                green: vec![0, 0, 0x08, 0, 0],
+            })
+        );
+    }
+
+    #[test]
+    fn hex_is_case_insensitive() {
+        assert_eq!(decode("aB", ""), decode("ab", ""));
+    }
+
+    #[test]
+    fn missing_channel_is_off() {
+        assert_eq!(
+            decode("", "0102"),
+            Ok(Bitmap {
+                red: vec![0, 0],
+                green: vec![1, 2],
+            })
+        );
+        assert_eq!(
+            decode("0102", ""),
+            Ok(Bitmap {
+                red: vec![1, 2],
+                green: vec![0, 0],
+            })
+        );
+        assert_eq!(decode("", "").map(|b| b.width()), Ok(0));
+    }
+
+    #[test]
+    fn odd_number_of_digits() {
+        assert_eq!(
+            decode("7f4", ""),
+            Err("red channel: expected two hex digits per column".to_owned())
+        );
+    }
+
+    #[test]
+    fn invalid_hex() {
+        assert_eq!(
+            decode("00", "00zz"),
+            Err("green channel: invalid hex at position 2".to_owned())
+        );
+        assert_eq!(
+            decode("+f", ""),
+            Err("red channel: invalid hex at position 0".to_owned())
+        );
+    }
+
+    #[test]
+    fn channels_of_different_widths() {
+        assert_eq!(
+            decode("0000", "00"),
+            Err("red channel is 2 columns wide, but green channel is 1".to_owned())
+        );
+    }
+
+    #[test]
+    fn encodes_what_it_decodes() {
+        let bitmap = decode("7F0a", "00ff").unwrap();
+        let encoded = EncodedBitmap::from(bitmap.clone());
+
+        assert_eq!(encoded.red, "7f0a");
+        assert_eq!(encoded.green, "00ff");
+        assert_eq!(Bitmap::try_from(encoded), Ok(bitmap));
+    }
+
+    #[test]
+    fn sprite_duration() {
+        assert_eq!(
+            sprite(3, Repeat::None).duration(),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(
+            sprite(3, Repeat::Times(1)).duration(),
+            Some(Duration::from_millis(600))
+        );
+        assert_eq!(sprite(3, Repeat::Forever).duration(), None);
+    }
+
+    #[test]
+    fn sprite_repeated_too_often_loops_forever() {
+        assert_eq!(sprite(3, Repeat::Times(u32::MAX as usize)).duration(), None);
+        assert_eq!(sprite(3, Repeat::Times(usize::MAX)).duration(), None);
+
+        let mut slow = sprite(3, Repeat::Times(1));
+        slow.frame_duration = Duration::MAX;
+        assert_eq!(slow.duration(), None);
+        assert_eq!(slow.frame_at(Duration::from_secs(1)).unwrap().red[0], 0);
+    }
+
+    #[test]
+    fn sprite_frames_loop() {
+        let sprite = sprite(3, Repeat::Forever);
+        let frame = |ms| sprite.frame_at(Duration::from_millis(ms)).unwrap().red[0];
+
+        assert_eq!(frame(0), 0);
+        assert_eq!(frame(250), 2);
+        assert_eq!(frame(350), 0);
+    }
+
+    #[test]
+    fn finished_sprite_keeps_last_frame() {
+        let sprite = sprite(3, Repeat::Times(1));
+        let frame = |ms| sprite.frame_at(Duration::from_millis(ms)).unwrap().red[0];
+
+        assert_eq!(frame(450), 1);
+        assert_eq!(frame(600), 2);
+        assert_eq!(frame(10_000), 2);
+    }
+
+    #[test]
+    fn sprite_without_frames() {
+        assert_eq!(sprite(0, Repeat::Forever).frame_at(Duration::ZERO), None);
+    }
+}
@@ -4,6 +4,9 @@ use std::{
 };
 
 pub mod api;
+pub mod bitmap;
+pub mod markup;
+pub mod template;
 pub mod uart;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,23 +16,46 @@ pub struct Pixel {
 }
 
 impl Pixel {
-    const OFF: Pixel = Self {
-        red: Level::Off,
-        green: Level::Off,
-    };
+    pub const OFF: Pixel = Self::new(Level::Off, Level::Off);
+    pub const RED: Pixel = Self::new(Level::On, Level::Off);
+    pub const GREEN: Pixel = Self::new(Level::Off, Level::On);
+    pub const ORANGE: Pixel = Self::new(Level::On, Level::On);
+    pub const YELLOW: Pixel = Self::new(Level::Medium, Level::On);
+    pub const LIME: Pixel = Self::new(Level::Dim, Level::On);
+    pub const AMBER: Pixel = Self::new(Level::On, Level::Medium);
+
+    pub const fn new(red: Level, green: Level) -> Self {
+        Self { red, green }
+    }
+
+    /// The closest pixel a board with the given `depth` can show. Without levels, a
+    /// lit pixel stays lit and keeps roughly the same hue.
+    pub fn with_depth(self, depth: ColorDepth) -> Self {
+        match depth {
+            ColorDepth::Levels => self,
+            ColorDepth::OnOff => {
+                let brightest = self.red.max(self.green).intensity();
+                let on_off = |level: Level| {
+                    if level != Level::Off && level.intensity() * 2 > brightest {
+                        Level::On
+                    } else {
+                        Level::Off
+                    }
+                };
+
+                Self::new(on_off(self.red), on_off(self.green))
+            }
+        }
+    }
 
     fn serialize(&self) -> u8 {
-        (if self.red == Level::On { 1 } else { 0 }) + (if self.green == Level::On { 2 } else { 0 })
+        self.red.intensity() | (self.green.intensity() << Level::BITS)
     }
 
     fn deserialize(x: u8) -> Self {
         Self {
-            red: if x & 1 == 1 { Level::On } else { Level::Off },
-            green: if (x << 1) & 1 == 1 {
-                Level::On
-            } else {
-                Level::Off
-            },
+            red: Level::from_intensity(x),
+            green: Level::from_intensity(x >> Level::BITS),
         }
     }
 }
@@ -40,10 +66,54 @@ impl Default for Pixel {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How brightly one color channel of a pixel is lit. The driver shows the levels in
+/// between off and on by lighting the pixel for only part of each frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
 pub enum Level {
-    Off,
-    On,
+    Off = 0,
+    Dim = 1,
+    Medium = 2,
+    On = 3,
+}
+
+impl Level {
+    /// Bits needed to store every level, i.e. the number of sub-frames the driver draws
+    pub const BITS: u32 = 2;
+
+    pub fn intensity(self) -> u8 {
+        self as u8
+    }
+
+    /// Only looks at the lowest `BITS` bits
+    pub fn from_intensity(x: u8) -> Self {
+        match x & ((1 << Self::BITS) - 1) {
+            0 => Level::Off,
+            1 => Level::Dim,
+            2 => Level::Medium,
+            _ => Level::On,
+        }
+    }
+}
+
+/// Which levels a board can draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorDepth {
+    /// Every level, drawn with one sub-frame per bit
+    #[default]
+    Levels,
+    /// Only `Level::Off` and `Level::On`, which leaves red, green and orange
+    OnOff,
+}
+
+impl ColorDepth {
+    /// How many of the most significant bits of each level are drawn
+    pub fn bits(self) -> u32 {
+        match self {
+            ColorDepth::Levels => Level::BITS,
+            ColorDepth::OnOff => 1,
+        }
+    }
 }
 
 const DISPLAY_WIDTH: usize = 80;
@@ -94,32 +164,23 @@ impl ScreenBuffer {
 }
 
 impl Display for ScreenBuffer {
+    /// Shows each pixel as `R`, `G` or `O` by the channels that are lit, in lowercase
+    /// if neither channel is fully on
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let red = Pixel {
-            red: Level::On,
-            green: Level::Off,
-        };
-        let green = Pixel {
-            red: Level::Off,
-            green: Level::On,
-        };
-        let orange = Pixel {
-            red: Level::On,
-            green: Level::On,
-        };
-
         for row in 0..DISPLAY_HEIGHT {
             for col in 0..DISPLAY_WIDTH {
-                if self[row][col] == Pixel::OFF {
-                    write!(f, ".")?;
-                } else if self[row][col] == red {
-                    write!(f, "R")?;
-                } else if self[row][col] == green {
-                    write!(f, "G")?;
-                } else if self[row][col] == orange {
-                    write!(f, "O")?;
+                let Pixel { red, green } = self[row][col];
+                let c = match (red != Level::Off, green != Level::Off) {
+                    (false, false) => '.',
+                    (true, false) => 'R',
+                    (false, true) => 'G',
+                    (true, true) => 'O',
+                };
+
+                if red.max(green) == Level::On {
+                    write!(f, "{}", c)?;
                 } else {
-                    write!(f, "?")?;
+                    write!(f, "{}", c.to_ascii_lowercase())?;
                 }
             }
             write!(f, "\n")?;
@@ -0,0 +1,287 @@
+// A small inline markup language for styling text without building spans by hand:
+//
+//   {red}Alert{/} {icon:heart} {{literal braces}}
+//
+// - `{red}`, `{dim_green}`, `{#ff8800}` or any other color (see `api::Color`) switch the
+//   color until the matching `{/}`
+// - `{/}` returns to the color that was active before the last color tag
+// - `{icon:name}` inserts a named glyph (resolved by the driver)
+// - `{time}`, `{time:%H:%M}`, `{date}`, `{date:%d.%m}`, `{countdown:14:00}`, `{uptime}`
+//   and `{counter:name}` insert text that updates while it is shown (see `prolite::template`)
+// - `{{` and `}}` are a literal `{` and `}`
+
+use std::fmt::Display;
+
+use crate::{
+    api::{Color, Span},
+    template,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkupError {
+    /// Byte offset into the markup where the error was detected
+    pub position: usize,
+    pub kind: MarkupErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkupErrorKind {
+    UnclosedTag,
+    UnexpectedClosingBrace,
+    UnknownTag(String),
+    UnmatchedEndTag,
+    EmptyIconName,
+    InvalidPlaceholder(String),
+}
+
+impl Display for MarkupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            MarkupErrorKind::UnclosedTag => write!(f, "unclosed tag")?,
+            MarkupErrorKind::UnexpectedClosingBrace => {
+                write!(f, "unexpected '}}' (use '}}}}' for a literal brace)")?
+            }
+            MarkupErrorKind::UnknownTag(tag) => write!(f, "unknown tag '{}'", tag)?,
+            MarkupErrorKind::UnmatchedEndTag => write!(f, "'{{/}}' without a matching color tag")?,
+            MarkupErrorKind::EmptyIconName => write!(f, "icon tag without a name")?,
+            MarkupErrorKind::InvalidPlaceholder(e) => write!(f, "invalid placeholder: {}", e)?,
+        }
+
+        write!(f, " at position {}", self.position)
+    }
+}
+
+impl std::error::Error for MarkupError {}
+
+/// Parses `markup` into spans. Text outside of any color tag uses `base_color`.
+pub fn parse(markup: &str, base_color: Option<Color>) -> Result<Vec<Span>, MarkupError> {
+    let mut spans = vec![];
+    let mut colors = vec![];
+    let mut text = String::new();
+
+    let current_color = |colors: &Vec<Color>| colors.last().copied().or(base_color);
+
+    let mut chars = markup.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '{' if chars.next_if(|(_, c)| *c == '{').is_some() => text.push('{'),
+            '}' if chars.next_if(|(_, c)| *c == '}').is_some() => text.push('}'),
+            '}' => {
+                return Err(MarkupError {
+                    position,
+                    kind: MarkupErrorKind::UnexpectedClosingBrace,
+                })
+            }
+            '{' => {
+                let mut tag = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, c)) => tag.push(c),
+                        None => {
+                            return Err(MarkupError {
+                                position,
+                                kind: MarkupErrorKind::UnclosedTag,
+                            })
+                        }
+                    }
+                }
+
+                let error = |kind| MarkupError { position, kind };
+
+                // every tag ends the current run of text
+                push_text(&mut spans, &mut text, current_color(&colors));
+
+                if tag == "/" {
+                    if colors.pop().is_none() {
+                        return Err(error(MarkupErrorKind::UnmatchedEndTag));
+                    }
+                } else if let Some(name) = tag.strip_prefix("icon:") {
+                    if name.is_empty() {
+                        return Err(error(MarkupErrorKind::EmptyIconName));
+                    }
+
+                    spans.push(Span::Icon {
+                        icon: name.to_owned(),
+                        color: current_color(&colors),
+                    });
+                } else if let Some(placeholder) = template::parse_tag(&tag)
+                    .map_err(|e| error(MarkupErrorKind::InvalidPlaceholder(e)))?
+                {
+                    spans.push(Span::Placeholder {
+                        placeholder,
+                        color: current_color(&colors),
+                    });
+                } else if let Some(color) = Color::from_name(&tag) {
+                    colors.push(color);
+                } else {
+                    return Err(error(MarkupErrorKind::UnknownTag(tag)));
+                }
+            }
+            c => text.push(c),
+        }
+    }
+
+    push_text(&mut spans, &mut text, current_color(&colors));
+
+    Ok(spans)
+}
+
+fn push_text(spans: &mut Vec<Span>, text: &mut String, color: Option<Color>) {
+    if text.is_empty() {
+        return;
+    }
+
+    let text = std::mem::take(text);
+
+    // merge with the previous span so "{red}a{/}{red}b{/}" renders as one run
+    if let Some(Span::Text {
+        text: previous_text,
+        color: previous_color,
+    }) = spans.last_mut()
+    {
+        if *previous_color == color {
+            previous_text.push_str(&text);
+            return;
+        }
+    }
+
+    spans.push(Span::Text { text, color });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::Placeholder;
+
+    fn text(text: &str, color: Option<Color>) -> Span {
+        Span::Text {
+            text: text.to_owned(),
+            color,
+        }
+    }
+
+    fn error_kind(markup: &str) -> MarkupErrorKind {
+        parse(markup, None).unwrap_err().kind
+    }
+
+    #[test]
+    fn plain_text_uses_base_color() {
+        assert_eq!(
+            parse("hello", Some(Color::Green)).unwrap(),
+            vec![text("hello", Some(Color::Green))]
+        );
+    }
+
+    #[test]
+    fn nested_colors_return_to_the_outer_color() {
+        assert_eq!(
+            parse("a{red}b{green}c{/}d{/}e", None).unwrap(),
+            vec![
+                text("a", None),
+                text("b", Some(Color::Red)),
+                text("c", Some(Color::Green)),
+                text("d", Some(Color::Red)),
+                text("e", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn adjacent_runs_of_the_same_color_are_merged() {
+        assert_eq!(
+            parse("{red}a{/}{red}b{/}", None).unwrap(),
+            vec![text("ab", Some(Color::Red))]
+        );
+    }
+
+    #[test]
+    fn color_tags_can_stay_open_until_the_end() {
+        assert_eq!(
+            parse("{red}a", None).unwrap(),
+            vec![text("a", Some(Color::Red))]
+        );
+    }
+
+    #[test]
+    fn escaped_braces() {
+        assert_eq!(parse("{{a}}", None).unwrap(), vec![text("{a}", None)]);
+    }
+
+    #[test]
+    fn icons_and_placeholders_take_the_current_color() {
+        assert_eq!(
+            parse("{green}{icon:heart}{uptime}", None).unwrap(),
+            vec![
+                Span::Icon {
+                    icon: "heart".to_owned(),
+                    color: Some(Color::Green),
+                },
+                Span::Placeholder {
+                    placeholder: Placeholder::Uptime,
+                    color: Some(Color::Green),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_icons_are_left_for_the_driver() {
+        assert_eq!(
+            parse("{icon:no_such_icon}", None).unwrap(),
+            vec![Span::Icon {
+                icon: "no_such_icon".to_owned(),
+                color: None,
+            }]
+        );
+        assert_eq!(error_kind("{icon:}"), MarkupErrorKind::EmptyIconName);
+    }
+
+    #[test]
+    fn unknown_colors_are_rejected() {
+        assert_eq!(
+            error_kind("{blue}a{/}"),
+            MarkupErrorKind::UnknownTag("blue".to_owned())
+        );
+        assert_eq!(
+            error_kind("{#12345}a"),
+            MarkupErrorKind::UnknownTag("#12345".to_owned())
+        );
+    }
+
+    #[test]
+    fn hex_colors() {
+        assert_eq!(
+            parse("{#ff8800}a", None).unwrap(),
+            vec![text("a", Some(Color::Rgb(0xff, 0x88, 0x00)))]
+        );
+    }
+
+    #[test]
+    fn unterminated_tag() {
+        let error = parse("ab{red", None).unwrap_err();
+        assert_eq!(error.kind, MarkupErrorKind::UnclosedTag);
+        assert_eq!(error.position, 2);
+    }
+
+    #[test]
+    fn unexpected_closing_brace() {
+        let error = parse("a}b", None).unwrap_err();
+        assert_eq!(error.kind, MarkupErrorKind::UnexpectedClosingBrace);
+        assert_eq!(error.position, 1);
+    }
+
+    #[test]
+    fn unmatched_end_tag() {
+        assert_eq!(error_kind("{red}a{/}{/}"), MarkupErrorKind::UnmatchedEndTag);
+    }
+
+    #[test]
+    fn invalid_placeholder() {
+        assert!(matches!(
+            error_kind("{countdown:25:00}"),
+            MarkupErrorKind::InvalidPlaceholder(_)
+        ));
+    }
+}
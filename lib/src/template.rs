@@ -0,0 +1,351 @@
+// Placeholders whose text changes while they are on screen. The driver evaluates
+// them every frame and only re-renders when the resulting text changes.
+
+use std::{collections::HashMap, fmt::Write, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Placeholder {
+    /// Wall clock time, formatted with a subset of strftime (see `format_time`)
+    Time {
+        #[serde(default = "default_time_format")]
+        format: String,
+    },
+    Date {
+        #[serde(default = "default_date_format")]
+        format: String,
+    },
+    /// Time left until the next occurrence of `hour:minute` local time
+    Countdown {
+        hour: u8,
+        minute: u8,
+    },
+    Uptime,
+    /// A value set with `Command::SetCounter`; unset counters show as 0
+    Counter {
+        name: String,
+    },
+}
+
+pub fn default_time_format() -> String {
+    "%H:%M".to_owned()
+}
+
+pub fn default_date_format() -> String {
+    "%Y-%m-%d".to_owned()
+}
+
+#[derive(Debug)]
+pub struct TemplateContext<'a> {
+    /// Seconds since the unix epoch, already adjusted to local time. `None` until
+    /// the driver knows what time it is, in which case times are shown as dashes.
+    pub local_time: Option<i64>,
+    pub uptime: Duration,
+    pub counters: &'a HashMap<String, i64>,
+}
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+impl Placeholder {
+    pub fn evaluate(&self, context: &TemplateContext) -> String {
+        match self {
+            Placeholder::Time { format } | Placeholder::Date { format } => {
+                format_time(context.local_time, format)
+            }
+            Placeholder::Countdown { hour, minute } => match context.local_time {
+                Some(local_time) => {
+                    let target = *hour as i64 * 3600 + *minute as i64 * 60;
+                    let remaining = (target - local_time).rem_euclid(SECONDS_PER_DAY);
+                    format_duration(Duration::from_secs(remaining as u64))
+                }
+                None => "--:--".to_owned(),
+            },
+            Placeholder::Uptime => format_duration(context.uptime),
+            Placeholder::Counter { name } => {
+                context.counters.get(name).copied().unwrap_or(0).to_string()
+            }
+        }
+    }
+}
+
+/// Parses the body of a markup tag such as `time:%H:%M` or `countdown:14:00`.
+/// Returns `Ok(None)` if the tag is not a placeholder at all.
+pub fn parse_tag(tag: &str) -> Result<Option<Placeholder>, String> {
+    let (name, argument) = match tag.split_once(':') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (tag, None),
+    };
+
+    let placeholder = match (name, argument) {
+        ("time", format) => Placeholder::Time {
+            format: format.map_or_else(default_time_format, str::to_owned),
+        },
+        ("date", format) => Placeholder::Date {
+            format: format.map_or_else(default_date_format, str::to_owned),
+        },
+        ("countdown", Some(target)) => {
+            let parsed = target
+                .split_once(':')
+                .and_then(|(h, m)| Some((h.parse::<u8>().ok()?, m.parse::<u8>().ok()?)));
+
+            match parsed {
+                Some((hour, minute)) if hour < 24 && minute < 60 => {
+                    Placeholder::Countdown { hour, minute }
+                }
+                _ => {
+                    return Err(format!(
+                        "'{}' is not a time of day (expected HH:MM)",
+                        target
+                    ))
+                }
+            }
+        }
+        ("countdown", None) => return Err("countdown needs a time, e.g. countdown:14:00".into()),
+        ("uptime", None) => Placeholder::Uptime,
+        ("counter", Some(name)) if !name.is_empty() => Placeholder::Counter {
+            name: name.to_owned(),
+        },
+        ("counter", _) => return Err("counter needs a name, e.g. counter:visitors".into()),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(placeholder))
+}
+
+/// Supports `%H %I %M %S %p %Y %y %m %d %a %b %%`. Without a time, every field is
+/// replaced by dashes so the text keeps roughly the same shape.
+pub fn format_time(local_time: Option<i64>, format: &str) -> String {
+    const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let fields = local_time.map(|t| {
+        let days = t.div_euclid(SECONDS_PER_DAY);
+        let seconds = t.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        // 1970-01-01 was a thursday
+        let weekday = (days + 4).rem_euclid(7) as usize;
+
+        (
+            year,
+            month,
+            day,
+            weekday,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+        )
+    });
+
+    let mut result = String::new();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        let Some(specifier) = chars.next() else {
+            result.push('%');
+            break;
+        };
+
+        let Some((year, month, day, weekday, hour, minute, second)) = fields else {
+            result.push_str(match specifier {
+                '%' => "%",
+                'Y' => "----",
+                'a' | 'b' => "---",
+                'H' | 'I' | 'M' | 'S' | 'p' | 'y' | 'm' | 'd' => "--",
+                _ => "",
+            });
+            continue;
+        };
+
+        // writing to a string never fails
+        let _ = match specifier {
+            'H' => write!(result, "{:02}", hour),
+            'I' => write!(result, "{:02}", (hour + 11) % 12 + 1),
+            'M' => write!(result, "{:02}", minute),
+            'S' => write!(result, "{:02}", second),
+            'p' => write!(result, "{}", if hour < 12 { "AM" } else { "PM" }),
+            'Y' => write!(result, "{}", year),
+            'y' => write!(result, "{:02}", year.rem_euclid(100)),
+            'm' => write!(result, "{:02}", month),
+            'd' => write!(result, "{:02}", day),
+            'a' => write!(result, "{}", WEEKDAYS[weekday]),
+            'b' => write!(result, "{}", MONTHS[month as usize - 1]),
+            '%' => write!(result, "%"),
+            c => write!(result, "%{}", c),
+        };
+    }
+
+    result
+}
+
+/// `M:SS`, `H:MM:SS`, or `Dd H:MM:SS`
+pub fn format_duration(duration: Duration) -> String {
+    let total = duration.as_secs();
+    let (days, hours, minutes, seconds) = (
+        total / 86400,
+        total / 3600 % 24,
+        total / 60 % 60,
+        total % 60,
+    );
+
+    if days > 0 {
+        format!("{}d {}:{:02}:{:02}", days, hours, minutes, seconds)
+    } else if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-02-29 13:05:09 UTC, a thursday
+    const LEAP_DAY: i64 = 1709211909;
+
+    #[test]
+    fn civil_from_days_around_the_epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(365), (1971, 1, 1));
+    }
+
+    #[test]
+    fn civil_from_days_leap_years() {
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19783), (2024, 3, 1));
+        // divisible by 100 but not by 400, so not a leap year
+        assert_eq!(civil_from_days(-25509), (1900, 2, 28));
+        assert_eq!(civil_from_days(-25508), (1900, 3, 1));
+        assert_eq!(civil_from_days(-135081), (1600, 2, 29));
+    }
+
+    #[test]
+    fn format_time_fields() {
+        assert_eq!(
+            format_time(Some(LEAP_DAY), "%Y-%m-%d %H:%M:%S"),
+            "2024-02-29 13:05:09"
+        );
+        assert_eq!(
+            format_time(Some(LEAP_DAY), "%a %d %b %y, %I%p"),
+            "Thu 29 Feb 24, 01PM"
+        );
+        assert_eq!(format_time(Some(LEAP_DAY), "100%% %q"), "100% %q");
+    }
+
+    #[test]
+    fn format_time_before_the_epoch() {
+        assert_eq!(
+            format_time(Some(-1), "%Y-%m-%d %H:%M:%S %a"),
+            "1969-12-31 23:59:59 Wed"
+        );
+    }
+
+    #[test]
+    fn format_time_without_a_time() {
+        assert_eq!(format_time(None, "%H:%M"), "--:--");
+        assert_eq!(format_time(None, "%a %Y-%m-%d"), "--- ----------");
+    }
+
+    #[test]
+    fn format_time_trailing_percent() {
+        assert_eq!(format_time(Some(0), "%H%"), "00%");
+    }
+
+    #[test]
+    fn format_duration_units() {
+        assert_eq!(format_duration(Duration::from_secs(65)), "1:05");
+        assert_eq!(format_duration(Duration::from_secs(3600 + 61)), "1:01:01");
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86400 + 3 * 3600 + 4 * 60 + 5)),
+            "2d 3:04:05"
+        );
+    }
+
+    #[test]
+    fn parse_tag_placeholders() {
+        assert_eq!(
+            parse_tag("time"),
+            Ok(Some(Placeholder::Time {
+                format: default_time_format()
+            }))
+        );
+        assert_eq!(
+            parse_tag("date:%d.%m"),
+            Ok(Some(Placeholder::Date {
+                format: "%d.%m".to_owned()
+            }))
+        );
+        assert_eq!(
+            parse_tag("countdown:14:00"),
+            Ok(Some(Placeholder::Countdown {
+                hour: 14,
+                minute: 0
+            }))
+        );
+        assert_eq!(parse_tag("uptime"), Ok(Some(Placeholder::Uptime)));
+        assert_eq!(
+            parse_tag("counter:visitors"),
+            Ok(Some(Placeholder::Counter {
+                name: "visitors".to_owned()
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_tag_other_tags() {
+        assert_eq!(parse_tag("red"), Ok(None));
+        assert_eq!(parse_tag("uptime:x"), Ok(None));
+    }
+
+    #[test]
+    fn parse_tag_invalid() {
+        assert!(parse_tag("countdown").is_err());
+        assert!(parse_tag("countdown:24:00").is_err());
+        assert!(parse_tag("countdown:12:60").is_err());
+        assert!(parse_tag("countdown:noon").is_err());
+        assert!(parse_tag("counter").is_err());
+        assert!(parse_tag("counter:").is_err());
+    }
+
+    #[test]
+    fn countdown_wraps_around_midnight() {
+        let counters = HashMap::new();
+        let context = TemplateContext {
+            local_time: Some(23 * 3600 + 30 * 60),
+            uptime: Duration::ZERO,
+            counters: &counters,
+        };
+
+        let countdown = Placeholder::Countdown {
+            hour: 0,
+            minute: 15,
+        };
+        assert_eq!(countdown.evaluate(&context), "45:00");
+    }
+}